@@ -0,0 +1,94 @@
+use std::collections::{HashMap, HashSet};
+use git2::{Oid, Repository, Sort};
+use crate::git::{AuthorResolver, diff_history};
+
+// Degree-of-authorship weights from Fritz et al., the model behind the
+// truck factor algorithm of Avelino et al.
+const DOA_BASE: f64 = 3.293;
+const DOA_FIRST_AUTHORSHIP: f64 = 1.098;
+const DOA_DELIVERIES: f64 = 0.164;
+const DOA_ACCEPTANCES: f64 = 0.321;
+/// Authors need at least this fraction of the highest degree on the file
+const AUTHOR_NORMALIZED_DOA: f64 = 0.75;
+
+/// Who created a file and how many commits each developer made to it
+#[derive(Debug, Default)]
+pub struct FileHistory {
+    creator: Option<String>,
+    deliveries: HashMap<String, usize>,
+    /// The name and email of everyone who committed to the file
+    contributors: HashSet<(String, String)>,
+}
+
+impl FileHistory {
+    /// First authorship and own commits raise the degree, commits by others lower it
+    fn degree(&self, developer: &str) -> f64 {
+        let first_authorship = if self.creator.as_deref() == Some(developer) { 1.0 } else { 0.0 };
+        let deliveries = self.deliveries.get(developer).copied().unwrap_or(0);
+        let acceptances = self.deliveries.values().sum::<usize>() - deliveries;
+
+        DOA_BASE + DOA_FIRST_AUTHORSHIP * first_authorship + DOA_DELIVERIES * deliveries as f64
+            - DOA_ACCEPTANCES * (1.0 + acceptances as f64).ln()
+    }
+
+    /// Developers who know the file well enough to count as its authors
+    pub fn authors(&self) -> Vec<String> {
+        let degrees: Vec<(&String, f64)> = self
+            .deliveries
+            .keys()
+            .map(|developer| (developer, self.degree(developer)))
+            .collect();
+        let highest = degrees.iter().map(|(_, degree)| *degree).fold(0.0, f64::max);
+
+        degrees
+            .into_iter()
+            .filter(|(_, degree)| *degree >= DOA_BASE && *degree / highest > AUTHOR_NORMALIZED_DOA)
+            .map(|(developer, _)| developer.clone())
+            .collect()
+    }
+
+    pub fn contributors(&self) -> impl Iterator<Item = &(String, String)> {
+        self.contributors.iter()
+    }
+}
+
+/// Replays the history leading to `commit`, oldest first, following renames
+/// so every file in its tree gets its whole history
+pub fn file_histories(repo: &Repository, commit: Oid) -> Result<HashMap<String, FileHistory>, git2::Error> {
+    let resolver = AuthorResolver::new(repo);
+    let mut revwalk = repo.revwalk()?;
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME | Sort::REVERSE)?;
+    revwalk.push(commit)?;
+
+    let mut commits = Vec::new();
+    for oid in revwalk {
+        let oid = oid?;
+        if repo.find_commit(oid)?.parent_count() <= 1 {
+            commits.push(oid);
+        }
+    }
+
+    // Diffing is the slow part and runs on all cores; the replay is in order
+    let mut histories: HashMap<String, FileHistory> = HashMap::new();
+    for commit in diff_history(repo, &commits)? {
+        let identity = resolver.resolve_identity(&commit.name, &commit.email);
+        let developer = identity.0.clone();
+
+        for file in commit.files {
+            if file.deleted {
+                histories.remove(&file.path);
+                continue;
+            }
+            if let Some(history) = file.old_path.and_then(|old_path| histories.remove(&old_path)) {
+                histories.insert(file.path.clone(), history);
+            }
+
+            let history = histories.entry(file.path).or_default();
+            history.creator.get_or_insert_with(|| developer.clone());
+            *history.deliveries.entry(developer.clone()).or_insert(0) += 1;
+            history.contributors.insert(identity.clone());
+        }
+    }
+
+    Ok(histories)
+}
@@ -0,0 +1,203 @@
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime};
+use git2::{BlameOptions, ObjectType, Oid, Repository};
+use indicatif::{ProgressBar, ProgressStyle};
+use serde::{Deserialize, Serialize};
+
+/// Who last touched each line of a committed file. The same content at the
+/// same path blames the same way, so this is cached on disk.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BlamedBlob {
+    /// Name and email, before mailmap resolution
+    pub authors: Vec<(String, String)>,
+    /// Index into `authors` for every line
+    pub lines: Vec<usize>,
+}
+
+/// A file with its blame and content, ready to be counted
+pub struct BlamedFile {
+    pub path: String,
+    pub blame: BlamedBlob,
+    pub content: String,
+}
+
+/// Bumped whenever `BlamedBlob` changes shape, so old entries are never misread
+const CACHE_VERSION: &str = "v1";
+/// Entries that no run has read or written for this long are removed
+const CACHE_MAX_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// One JSON file per path and blob under `<git dir>/recap/blame/<version>`
+struct BlameCache {
+    root: PathBuf,
+    dir: PathBuf,
+}
+
+impl BlameCache {
+    fn new(repo: &Repository) -> Self {
+        let root = repo.path().join("recap").join("blame");
+        let dir = root.join(CACHE_VERSION);
+        Self { root, dir }
+    }
+
+    /// A blob blames differently at another path, whose history differs
+    fn entry(&self, path: &str, blob: Oid) -> PathBuf {
+        let key = Oid::hash_object(ObjectType::Blob, format!("{}\0{}", blob, path).as_bytes())
+            .unwrap_or(blob);
+        self.dir.join(format!("{}.json", key))
+    }
+
+    fn get(&self, path: &str, blob: Oid) -> Option<BlamedBlob> {
+        let entry = self.entry(path, blob);
+        let json = std::fs::read_to_string(&entry).ok()?;
+        let blame = serde_json::from_str(&json).ok()?;
+        // Entries in use are kept fresh so pruning only drops the stale ones
+        if let Ok(file) = File::options().write(true).open(&entry) {
+            let _ = file.set_modified(SystemTime::now());
+        }
+        Some(blame)
+    }
+
+    /// Best effort: a cache that cannot be written only costs time
+    fn put(&self, path: &str, blob: Oid, blame: &BlamedBlob) {
+        let Ok(json) = serde_json::to_string(blame) else {
+            return;
+        };
+        if std::fs::create_dir_all(&self.dir).is_err() {
+            return;
+        }
+        let entry = self.entry(path, blob);
+        // Written aside and renamed so a concurrent run never reads half an entry
+        let partial = entry.with_extension(format!("{}.tmp", std::process::id()));
+        if std::fs::write(&partial, json).is_ok() && std::fs::rename(&partial, entry).is_err() {
+            let _ = std::fs::remove_file(&partial);
+        }
+    }
+
+    /// Removes entries of older cache versions and entries unused for
+    /// `CACHE_MAX_AGE`, so the cache follows the files that still exist
+    fn prune(&self) {
+        let Ok(versions) = std::fs::read_dir(&self.root) else {
+            return;
+        };
+        for version in versions.flatten() {
+            if version.path() != self.dir {
+                let _ = std::fs::remove_dir_all(version.path());
+            }
+        }
+
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let stale = entry
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .and_then(|modified| modified.elapsed().ok())
+                .is_some_and(|age| age > CACHE_MAX_AGE);
+            if stale {
+                let _ = std::fs::remove_file(entry.path());
+            }
+        }
+    }
+}
+
+/// Blames `files` (repository-relative paths with their blob ids in `commit`)
+/// on all cores. Binary, empty and unblamable files are left out.
+pub fn blame_files(repo: &Repository, commit: Oid, files: &[(String, Oid)]) -> Result<Vec<BlamedFile>, git2::Error> {
+    let workers = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1).min(files.len().max(1));
+    // Handles are opened up front so a broken repository fails the run, not a worker
+    let repos = (0..workers)
+        .map(|_| Repository::open(repo.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let progress_bar = ProgressBar::new(files.len() as u64);
+    progress_bar.set_style(
+        ProgressStyle::default_bar()
+            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} files ({per_sec})")
+            .unwrap()
+            .progress_chars("#>-")
+    );
+    progress_bar.enable_steady_tick(Duration::from_millis(100));
+
+    let cache = BlameCache::new(repo);
+    // Blame times vary a lot between files, so workers take the next file as they go
+    let next = AtomicUsize::new(0);
+    let blamed = std::thread::scope(|scope| {
+        let handles: Vec<_> = repos
+            .into_iter()
+            .map(|repo| {
+                let (cache, next, progress_bar) = (&cache, &next, &progress_bar);
+                scope.spawn(move || {
+                    let mut blamed = Vec::new();
+                    while let Some((path, blob)) = files.get(next.fetch_add(1, Ordering::Relaxed)) {
+                        if let Some(file) = blame_file(&repo, cache, commit, path, *blob) {
+                            blamed.push(file);
+                        }
+                        progress_bar.inc(1);
+                    }
+                    blamed
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("Blame worker panicked"))
+            .collect()
+    });
+
+    progress_bar.finish_and_clear();
+    cache.prune();
+    Ok(blamed)
+}
+
+fn blame_file(repo: &Repository, cache: &BlameCache, commit: Oid, path: &str, blob_id: Oid) -> Option<BlamedFile> {
+    let blob = repo.find_blob(blob_id).ok()?;
+    if blob.is_binary() {
+        return None;
+    }
+    let content = std::str::from_utf8(blob.content()).ok()?.to_string();
+    if content.trim().is_empty() {
+        return None;
+    }
+
+    let blame = match cache.get(path, blob_id) {
+        Some(blame) => blame,
+        None => {
+            let blame = run_blame(repo, commit, path, content.lines().count()).ok()?;
+            cache.put(path, blob_id, &blame);
+            blame
+        }
+    };
+
+    Some(BlamedFile { path: path.to_string(), blame, content })
+}
+
+fn run_blame(repo: &Repository, commit: Oid, path: &str, line_count: usize) -> Result<BlamedBlob, git2::Error> {
+    let blame = repo.blame_file(Path::new(path), Some(BlameOptions::new().newest_commit(commit)))?;
+    let mut authors: Vec<(String, String)> = Vec::new();
+    let mut lines = Vec::with_capacity(line_count);
+
+    for line in 1..=line_count {
+        let Some(hunk) = blame.get_line(line) else {
+            break;
+        };
+        let signature = hunk.final_signature();
+        let author = (
+            signature.name().unwrap_or("Unknown").to_string(),
+            signature.email().unwrap_or("").to_string(),
+        );
+        let index = match authors.iter().position(|known| *known == author) {
+            Some(index) => index,
+            None => {
+                authors.push(author);
+                authors.len() - 1
+            }
+        };
+        lines.push(index);
+    }
+
+    Ok(BlamedBlob { authors, lines })
+}
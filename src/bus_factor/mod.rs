@@ -0,0 +1,539 @@
+mod authorship;
+mod blame;
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Component, Path, PathBuf};
+use colored::*;
+use git2::{Commit, ObjectType, Oid, Repository, TreeWalkMode, TreeWalkResult};
+use serde::Serialize;
+use crate::git::{AuthorFilter, AuthorResolver};
+use crate::lexer::{LineClassifier, LineKind};
+use crate::path_filter::{PathFilter, PathPatterns};
+use std::error::Error;
+
+pub struct BusFactorAnalyzer {
+    repo: Repository,
+    threshold: f64,
+    depth: usize,
+    paths: PathPatterns,
+}
+
+/// A file where one author wrote at least the threshold share of the lines
+#[derive(Debug, Clone, Serialize)]
+pub struct BusFactorResult {
+    pub path: String,
+    pub dominant_author: String,
+    pub ownership_percentage: f64,
+    pub total_lines: usize,
+}
+
+/// The bus factor of the analyzed path and of each directory below it
+#[derive(Debug, Clone, Serialize)]
+pub struct ModuleBusFactor {
+    pub path: String,
+    /// 0 for the analyzed path, 1 for the directories directly below it, and so on
+    pub depth: usize,
+    /// How many people would have to leave before most of the code has no author left
+    pub bus_factor: usize,
+    /// The people whose departure it would take, most knowledgeable first
+    pub key_people: Vec<String>,
+    pub file_count: usize,
+    pub total_lines: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BusFactorReport {
+    pub modules: Vec<ModuleBusFactor>,
+    pub single_owner_files: Vec<BusFactorResult>,
+}
+
+/// A file or directory that would be left mostly unowned if some people left
+#[derive(Debug, Clone, Serialize)]
+pub struct KnowledgeLoss {
+    pub path: String,
+    pub is_directory: bool,
+    pub total_lines: usize,
+    /// Lines written by the people leaving
+    pub lost_lines: usize,
+    /// Percentage of the lines written by people who stay
+    pub coverage: f64,
+    /// Who of the people staying wrote the most of it, or failing that, knows it best
+    pub candidate: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LeaveSimulation {
+    /// The "Name <email>" identities that matched and were simulated leaving
+    pub leaving: Vec<String>,
+    pub losses: Vec<KnowledgeLoss>,
+}
+
+/// A file with who wrote its current lines and who knows it
+struct KnownFile {
+    path: String,
+    total_lines: usize,
+    /// Lines per resolved name and email
+    author_lines: HashMap<(String, String), usize>,
+    /// Authors by degree of authorship, plus whoever wrote most of the lines
+    authors: Vec<String>,
+    /// Name and email of everyone who committed to the file
+    contributors: Vec<(String, String)>,
+}
+
+impl KnownFile {
+    /// Lines per person. People are told apart by name, like everywhere else.
+    fn lines_by_name(&self) -> HashMap<&str, usize> {
+        let mut lines: HashMap<&str, usize> = HashMap::new();
+        for ((name, _), count) in &self.author_lines {
+            *lines.entry(name).or_insert(0) += count;
+        }
+        lines
+    }
+
+    fn dominant(&self) -> BusFactorResult {
+        let (dominant_author, lines) = self
+            .lines_by_name()
+            .into_iter()
+            .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+            .map(|(author, lines)| (author.to_string(), lines))
+            .unwrap_or(("Unknown".to_string(), 0));
+
+        BusFactorResult {
+            path: self.path.clone(),
+            dominant_author,
+            ownership_percentage: (lines as f64 / self.total_lines as f64) * 100.0,
+            total_lines: self.total_lines,
+        }
+    }
+}
+
+impl BusFactorAnalyzer {
+    pub fn new(repo_path: &str, threshold: f64, depth: usize, paths: PathPatterns) -> Result<Self, Box<dyn Error>> {
+        // Try to find the git repository from the current path
+        let repo = match Repository::discover(repo_path) {
+            Ok(repo) => repo,
+            Err(e) => return Err(format!("Could not find git repository: {}", e).into()),
+        };
+        Ok(BusFactorAnalyzer { repo, threshold, depth, paths })
+    }
+
+    /// Analyzes `path` as committed at `rev`, or at HEAD
+    pub fn analyze_path(&self, path: &str, rev: Option<&str>) -> Result<BusFactorReport, Box<dyn Error>> {
+        let (root, files) = self.known_files(path, rev)?;
+
+        let mut single_owner_files: Vec<BusFactorResult> = files
+            .iter()
+            .map(KnownFile::dominant)
+            .filter(|result| result.ownership_percentage >= self.threshold)
+            .collect();
+        single_owner_files.sort_by(|a, b| {
+            b.ownership_percentage
+                .total_cmp(&a.ownership_percentage)
+                .then(b.total_lines.cmp(&a.total_lines))
+        });
+
+        let modules = self
+            .group(&root, &files)
+            .into_iter()
+            .map(|(path, depth, files)| module_bus_factor(&path, depth, &files))
+            .collect();
+
+        Ok(BusFactorReport { modules, single_owner_files })
+    }
+
+    /// Files and directories under `path` where less than `coverage` percent
+    /// of the lines would be left written by someone who stays, if the
+    /// authors matching `leaving` left. Directories come first.
+    pub fn simulate_leave(&self, path: &str, rev: Option<&str>, leaving: &[String], coverage: f64) -> Result<LeaveSimulation, Box<dyn Error>> {
+        let (root, files) = self.known_files(path, rev)?;
+
+        // Patterns match a name, an email or "Name <email>"; whoever matches leaves under every email
+        let filter = AuthorFilter::exact(leaving);
+        let mut matched: BTreeSet<String> = BTreeSet::new();
+        let mut leaving_names: BTreeSet<&str> = BTreeSet::new();
+        for (name, email) in files.iter().flat_map(|file| file.author_lines.keys().chain(&file.contributors)) {
+            let identity = format!("{} <{}>", name, email);
+            if filter.matches(&[name, email, &identity]) {
+                leaving_names.insert(name);
+                matched.insert(identity);
+            }
+        }
+        if matched.is_empty() {
+            return Err(format!("Nobody who worked on '{}' matches {}", path, leaving.join(", ")).into());
+        }
+        let leaves = |author: &str| leaving_names.contains(author);
+
+        let directories = self
+            .group(&root, &files)
+            .into_iter()
+            // A single analyzed file is reported as a file
+            .filter(|(path, _, files)| !(files.len() == 1 && files[0].path == *path))
+            .filter_map(|(path, _, files)| knowledge_loss(path, true, &files, leaves, coverage));
+
+        let mut file_losses: Vec<KnowledgeLoss> = files
+            .iter()
+            .filter_map(|file| knowledge_loss(file.path.clone(), false, &[file], leaves, coverage))
+            .collect();
+        file_losses.sort_by(|a, b| a.coverage.total_cmp(&b.coverage).then(b.lost_lines.cmp(&a.lost_lines)));
+
+        let losses = directories.chain(file_losses).collect();
+        Ok(LeaveSimulation { leaving: matched.into_iter().collect(), losses })
+    }
+
+    /// Blames the files under `path` and works out who knows each of them.
+    /// Returns the repository-relative root with the files.
+    fn known_files(&self, path: &str, rev: Option<&str>) -> Result<(String, Vec<KnownFile>), Box<dyn Error>> {
+        let path = Path::new(path);
+        let filter = PathFilter::new(&self.repo, &self.paths, &[])?;
+
+        let workdir = self.repo.workdir().ok_or("Could not get repository working directory")?;
+        // Relative paths start at the repository root, as with hotspots
+        let target_path = if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            workdir.join(path)
+        };
+
+        let root = match normalize(&target_path).strip_prefix(workdir) {
+            Ok(relative) if relative.as_os_str().is_empty() => ".".to_string(),
+            Ok(relative) => relative.to_string_lossy().replace('\\', "/"),
+            Err(_) => return Err(format!("Path '{}' is outside the repository", path.display()).into()),
+        };
+
+        let commit = match rev {
+            Some(rev) => Some(
+                self.repo
+                    .revparse_single(rev)
+                    .and_then(|object| object.peel_to_commit())
+                    .map_err(|e| format!("Unknown revision '{}': {}", rev, e.message()))?,
+            ),
+            // An unborn HEAD simply has no files
+            None => self.repo.head().and_then(|head| head.peel_to_commit()).ok(),
+        };
+        let Some(commit) = commit else {
+            return Ok((root, Vec::new()));
+        };
+
+        let tree = commit.tree()?;
+        if root != "." && tree.get_path(Path::new(&root)).is_err() {
+            return Err(format!("Path '{}' does not exist at {}", path.display(), rev.unwrap_or("HEAD")).into());
+        }
+
+        let resolver = AuthorResolver::new(&self.repo);
+        let mut files: Vec<KnownFile> = blame::blame_files(&self.repo, commit.id(), &self.tracked_files(&commit, &filter, &root)?)?
+            .into_iter()
+            .filter_map(|file| count_code_lines(&resolver, file))
+            .collect();
+
+        let mut histories = authorship::file_histories(&self.repo, commit.id())?;
+        for file in &mut files {
+            if let Some(history) = histories.remove(&file.path) {
+                file.authors = history.authors();
+                file.contributors = history.contributors().cloned().collect();
+            }
+            // Whoever wrote most of what is there now knows it, whatever the history says
+            let dominant = file.dominant();
+            if dominant.ownership_percentage >= 50.0 && !file.authors.contains(&dominant.dominant_author) {
+                file.authors.push(dominant.dominant_author);
+            }
+        }
+
+        Ok((root, files))
+    }
+
+    /// The analyzed path and its directories, up to `depth` levels down, with
+    /// their depth below the analyzed path and the files they hold
+    fn group<'a>(&self, root: &str, files: &'a [KnownFile]) -> Vec<(String, usize, Vec<&'a KnownFile>)> {
+        let mut directories: BTreeMap<String, Vec<&KnownFile>> = BTreeMap::new();
+        for file in files {
+            let relative = if root == "." { file.path.as_str() } else { file.path.strip_prefix(root).unwrap_or(&file.path).trim_start_matches('/') };
+            let mut segments: Vec<&str> = relative.split('/').collect();
+            segments.pop();
+
+            let mut directory = if root == "." { String::new() } else { root.to_string() };
+            for segment in segments.into_iter().take(self.depth) {
+                if !directory.is_empty() {
+                    directory.push('/');
+                }
+                directory.push_str(segment);
+                directories.entry(directory.clone()).or_default().push(file);
+            }
+        }
+
+        let all: Vec<&KnownFile> = files.iter().collect();
+        let depth_below_root = |path: &str| {
+            let relative = if root == "." { path } else { path[root.len()..].trim_start_matches('/') };
+            relative.split('/').count()
+        };
+
+        std::iter::once((root.to_string(), 0, all))
+            .chain(directories.into_iter().map(|(path, files)| {
+                let depth = depth_below_root(&path);
+                (path, depth, files)
+            }))
+            .collect()
+    }
+
+    /// Files at or below `root` in the commit that pass the path filters, with their blob ids
+    fn tracked_files(&self, commit: &Commit, filter: &PathFilter, root: &str) -> Result<Vec<(String, Oid)>, git2::Error> {
+        let mut files = Vec::new();
+        commit.tree()?.walk(TreeWalkMode::PreOrder, |directory, entry| {
+            let Some(name) = entry.name() else {
+                return TreeWalkResult::Skip;
+            };
+            let path = format!("{}{}", directory, name);
+            let is_dir = entry.kind() == Some(ObjectType::Tree);
+
+            // Directories above the root are walked through, everything else must be below it
+            let leads_to_root = is_dir && is_within(root, &path);
+            if !(is_within(&path, root) || leads_to_root) {
+                return TreeWalkResult::Skip;
+            }
+            if !filter.allows(&path, is_dir) {
+                return TreeWalkResult::Skip;
+            }
+            // Symbolic links are blobs too, but hold a target rather than code
+            if entry.kind() == Some(ObjectType::Blob) && entry.filemode() != 0o120000 {
+                files.push((path, entry.id()));
+            }
+            TreeWalkResult::Ok
+        })?;
+
+        Ok(files)
+    }
+}
+
+/// Resolves `.` and `..` without touching the filesystem, since the path
+/// may only exist in the analyzed revision
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+fn is_within(path: &str, root: &str) -> bool {
+    root == "." || path == root || path.strip_prefix(root).is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// Lines per author, leaving out blank lines and comments
+fn count_code_lines(resolver: &AuthorResolver, file: blame::BlamedFile) -> Option<KnownFile> {
+    let authors: Vec<(String, String)> = file
+        .blame
+        .authors
+        .iter()
+        .map(|(name, email)| resolver.resolve_identity(name, email))
+        .collect();
+    let mut author_lines: HashMap<(String, String), usize> = HashMap::new();
+    let mut total_lines = 0;
+    let mut classifier = LineClassifier::for_path(Path::new(&file.path));
+
+    for (line, author) in file.content.lines().zip(&file.blame.lines) {
+        if classifier.classify(line) != LineKind::Code {
+            continue;
+        }
+        total_lines += 1;
+        *author_lines.entry(authors[*author].clone()).or_insert(0) += 1;
+    }
+
+    if total_lines == 0 {
+        return None;
+    }
+
+    Some(KnownFile { path: file.path, total_lines, author_lines, authors: Vec::new(), contributors: Vec::new() })
+}
+
+/// The smallest group of people whose departure leaves more than half of the
+/// lines without anyone who authored them, picked greedily: the person who
+/// knows the most of what is still known leaves first
+fn module_bus_factor(path: &str, depth: usize, files: &[&KnownFile]) -> ModuleBusFactor {
+    let total_lines: usize = files.iter().map(|file| file.total_lines).sum();
+    let mut key_people: Vec<String> = Vec::new();
+
+    loop {
+        let is_orphaned = |file: &&&KnownFile| file.authors.iter().all(|author| key_people.contains(author));
+        let orphaned: usize = files.iter().filter(is_orphaned).map(|file| file.total_lines).sum();
+        if orphaned * 2 > total_lines {
+            break;
+        }
+
+        let mut knowledge: HashMap<&str, usize> = HashMap::new();
+        for file in files.iter().filter(|file| !is_orphaned(file)) {
+            for author in file.authors.iter().filter(|author| !key_people.contains(author)) {
+                *knowledge.entry(author).or_insert(0) += file.total_lines;
+            }
+        }
+        let Some((next, _)) = knowledge.into_iter().max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0))) else {
+            break;
+        };
+        key_people.push(next.to_string());
+    }
+
+    ModuleBusFactor {
+        path: path.to_string(),
+        depth,
+        bus_factor: key_people.len(),
+        key_people,
+        file_count: files.len(),
+        total_lines,
+    }
+}
+
+fn knowledge_loss(
+    path: String,
+    is_directory: bool,
+    files: &[&KnownFile],
+    leaves: impl Fn(&str) -> bool,
+    coverage: f64,
+) -> Option<KnowledgeLoss> {
+    let total_lines: usize = files.iter().map(|file| file.total_lines).sum();
+    let lost_lines: usize = files
+        .iter()
+        .flat_map(|file| file.lines_by_name())
+        .filter(|(author, _)| leaves(author))
+        .map(|(_, lines)| lines)
+        .sum();
+    if lost_lines == 0 || total_lines == 0 {
+        return None;
+    }
+
+    let remaining = (total_lines - lost_lines) as f64 * 100.0 / total_lines as f64;
+    if remaining >= coverage {
+        return None;
+    }
+
+    Some(KnowledgeLoss {
+        path,
+        is_directory,
+        total_lines,
+        lost_lines,
+        coverage: remaining,
+        candidate: best_candidate(files, leaves),
+    })
+}
+
+/// The person staying who wrote the most lines, or if nobody staying wrote
+/// any, the one who counts as an author of the most files
+fn best_candidate(files: &[&KnownFile], leaves: impl Fn(&str) -> bool) -> Option<String> {
+    let mut lines: HashMap<&str, usize> = HashMap::new();
+    let mut authorships: HashMap<&str, usize> = HashMap::new();
+    for file in files {
+        for (author, count) in file.lines_by_name().into_iter().filter(|(author, _)| !leaves(author)) {
+            *lines.entry(author).or_insert(0) += count;
+        }
+        for author in file.authors.iter().filter(|author| !leaves(author)) {
+            *authorships.entry(author).or_insert(0) += 1;
+        }
+    }
+
+    let most = |counts: HashMap<&str, usize>| {
+        counts
+            .into_iter()
+            .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+            .map(|(author, _)| author.to_string())
+    };
+    most(lines).or_else(|| most(authorships))
+}
+
+pub fn format_bus_factor_report(
+    modules: &[ModuleBusFactor],
+    single_owner_files: &[BusFactorResult],
+    threshold: f64,
+    rev: Option<&str>,
+) -> String {
+    if modules.iter().all(|module| module.file_count == 0) {
+        return "No files found to analyze.".yellow().to_string();
+    }
+
+    let mut output = match rev {
+        Some(rev) => format!("\nBus Factor at {}:\n", rev).bold().to_string(),
+        None => "\nBus Factor:\n".bold().to_string(),
+    };
+
+    for module in modules.iter().filter(|module| module.file_count > 0) {
+        let bus_factor = module.bus_factor.to_string();
+        let bus_factor = match module.bus_factor {
+            0 | 1 => bus_factor.red(),
+            2 => bus_factor.yellow(),
+            _ => bus_factor.green(),
+        };
+        output.push_str(&format!(
+            "{}{}  bus factor {} ({} files, {} lines)\n",
+            "  ".repeat(module.depth + 1),
+            module.path.blue(),
+            bus_factor,
+            module.file_count,
+            module.total_lines
+        ));
+        if !module.key_people.is_empty() {
+            output.push_str(&format!(
+                "{}  Key people: {}\n",
+                "  ".repeat(module.depth + 1),
+                module.key_people.join(", ").green()
+            ));
+        }
+    }
+
+    if !single_owner_files.is_empty() {
+        output.push_str(&format!("\nFiles at least {:.0}% written by one person:\n", threshold));
+        for result in single_owner_files {
+            output.push_str(&format!(
+                "  - {} ({}% owned by {}, {} lines)\n",
+                result.path.blue(),
+                format!("{:.0}", result.ownership_percentage).red(),
+                result.dominant_author.green(),
+                result.total_lines
+            ));
+        }
+    }
+
+    output
+}
+
+pub fn format_leave_report(losses: &[KnowledgeLoss], leaving: &[String], coverage: f64) -> String {
+    if losses.is_empty() {
+        return format!(
+            "Everything would keep at least {:.0}% of its lines written by someone who stays if {} left.",
+            coverage,
+            leaving.join(", ")
+        )
+        .green()
+        .to_string();
+    }
+
+    let mut output = format!("\nIf {} left, less than {:.0}% would stay covered:\n", leaving.join(", "), coverage)
+        .bold()
+        .to_string();
+
+    for (heading, is_directory) in [("Directories", true), ("Files", false)] {
+        let entries: Vec<&KnowledgeLoss> = losses.iter().filter(|loss| loss.is_directory == is_directory).collect();
+        if entries.is_empty() {
+            continue;
+        }
+
+        output.push_str(&format!("\n{}:\n", heading));
+        for loss in entries {
+            let candidate = match &loss.candidate {
+                Some(candidate) => format!("best candidate: {}", candidate.green()),
+                None => "nobody left who knows it".red().to_string(),
+            };
+            output.push_str(&format!(
+                "  - {} ({}% covered, {} of {} lines lost), {}\n",
+                loss.path.blue(),
+                format!("{:.0}", loss.coverage).red(),
+                loss.lost_lines,
+                loss.total_lines,
+                candidate
+            ));
+        }
+    }
+
+    output
+}
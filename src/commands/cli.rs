@@ -1,4 +1,12 @@
-use clap::{Arg, Command as ClapCommand, Parser, Subcommand};
+use clap::{Parser, Subcommand};
+use chrono::{NaiveDate, Weekday};
+use crate::commands::config_file::ConfigFile;
+use crate::music::MusicConfig;
+use crate::hotspots::{CouplingOptions, HotspotGrouping, HotspotSort, SuggestionRule, TrendPeriod, default_rules};
+use crate::output::OutputFormat;
+use crate::path_filter::PathPatterns;
+use crate::period::{Calendar, SprintCalendar, WorkWeek};
+use crate::standup::Backend;
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
@@ -6,13 +14,17 @@ pub struct Cli {
     #[command(subcommand)]
     pub command: Option<Commands>,
 
-    /// Repository path
-    #[arg(short, long, default_value = ".")]
-    pub repo_path: String,
+    /// Repository path (repeat to recap several repositories, defaults to ".")
+    #[arg(short, long, global = true)]
+    pub repo_path: Vec<String>,
 
-    /// Author name
+    /// Recap every git repository found below this directory
     #[arg(short, long)]
-    pub author: Option<String>,
+    pub workspace: Option<String>,
+
+    /// Author name, email pattern or alias (repeat for several identities, defaults to git config user.name)
+    #[arg(short, long, global = true)]
+    pub author: Vec<String>,
 
     /// Show diff for each commit
     #[arg(short = 'd', long)]
@@ -26,9 +38,42 @@ pub struct Cli {
     #[arg(long = "save-music")]
     pub save_music_path: Option<String>,
 
-    /// Since date (e.g., "1 week ago", "2023-01-01")
-    #[arg(short, long, default_value = "24 hours ago")]
-    pub since: String,
+    /// Since date or period (e.g., "1 week ago", "2023-01-01", "yesterday", "last-week", "this-sprint");
+    /// "last-standup" (the default) starts at the previous working day
+    #[arg(short, long)]
+    pub since: Option<String>,
+
+    /// Until date or period, exclusive (e.g., "2023-01-31", "yesterday"); a bare date includes that whole day
+    #[arg(short, long, global = true)]
+    pub until: Option<String>,
+
+    /// First day of any sprint, used by "this-sprint" and "last-sprint"
+    #[arg(long, global = true, default_value = "2024-01-01")]
+    pub sprint_start: NaiveDate,
+
+    /// Sprint length in days
+    #[arg(long, global = true, default_value = "14")]
+    pub sprint_length: i64,
+
+    /// Working days, used by "last-standup" (defaults to mon,tue,wed,thu,fri)
+    #[arg(long, global = true, value_delimiter = ',', value_parser = parse_weekday)]
+    pub work_days: Vec<Weekday>,
+
+    /// Holiday to skip when looking for the previous working day (repeat or separate with commas)
+    #[arg(long = "holiday", global = true, value_delimiter = ',')]
+    pub holidays: Vec<NaiveDate>,
+
+    /// Only analyze paths matching this gitignore-style glob (repeatable)
+    #[arg(long, global = true)]
+    pub include: Vec<String>,
+
+    /// Skip paths matching this gitignore-style glob; "!glob" re-includes a default exclusion (repeatable)
+    #[arg(long, global = true, allow_hyphen_values = true)]
+    pub exclude: Vec<String>,
+
+    /// Output format (defaults to text)
+    #[arg(long, value_enum, global = true)]
+    pub format: Option<OutputFormat>,
 }
 
 #[derive(Subcommand)]
@@ -41,76 +86,330 @@ pub enum Commands {
         /// Since date (e.g., "1 week ago", "2023-01-01")
         #[arg(short, long, default_value = "all")]
         since: String,
+
+        /// How to rank the files
+        #[arg(long, value_enum, default_value_t = HotspotSort::Risk)]
+        sort_by: HotspotSort,
+
+        /// Number of files, or directories with `--group-by dir`, to report (defaults to 10 files in the text report, all otherwise)
+        #[arg(long)]
+        top: Option<usize>,
+
+        /// Report files, or roll them up into a directory tree
+        #[arg(long, value_enum, default_value_t = HotspotGrouping::File)]
+        group_by: HotspotGrouping,
+
+        /// How many directory levels the tree goes down with --group-by dir
+        #[arg(long, default_value = "2")]
+        depth: usize,
+
+        /// Also show how each file changed over consecutive calendar windows
+        #[arg(long, value_enum)]
+        trend: Option<TrendPeriod>,
+
+        /// Number of windows for --trend, ending with the current one
+        #[arg(long, default_value = "12")]
+        windows: usize,
+    },
+    /// Find files that tend to change in the same commits
+    Coupling {
+        /// Optional path to analyze (defaults to entire repository)
+        path: Option<String>,
+
+        /// Since date (e.g., "1 week ago", "2023-01-01")
+        #[arg(short, long, default_value = "all")]
+        since: String,
+
+        /// Only report pairs changed together at least this many times
+        #[arg(long, default_value = "3")]
+        min_shared: usize,
+
+        /// Ignore commits touching more files than this, such as bulk reformats
+        #[arg(long, default_value = "30")]
+        max_files: usize,
+    },
+    /// Recap everyone's commits, grouped per author
+    Team {
+        /// Since date (e.g., "1 week ago", "2023-01-01", defaults to "last-standup")
+        #[arg(short, long)]
+        since: Option<String>,
+    },
+    /// Summarize your recent commits into standup notes
+    Standup {
+        /// Since date (e.g., "1 week ago", "2023-01-01", defaults to "last-standup")
+        #[arg(short, long)]
+        since: Option<String>,
+        /// Include per-file diffstats in the summary
+        #[arg(long)]
+        diffstat: bool,
+        /// Summarizer backend
+        #[arg(long, value_enum, default_value_t = Backend::Ollama)]
+        backend: Backend,
+        /// Base URL of the Ollama-compatible server
+        #[arg(long, default_value = "http://localhost:11434")]
+        endpoint: String,
+        /// Model to ask for the summary
+        #[arg(long, default_value = "phi4")]
+        model: String,
     },
     /// Show who knows about a specific file
     WhoKnows {
         /// Path to the file to analyze
         path: String,
+
+        /// Since date (e.g., "1 week ago", "2023-01-01", "last-month")
+        #[arg(short, long, default_value = "all")]
+        since: String,
     },
     /// Analyze bus factor risks
     BusFactor {
-        /// Path to analyze (defaults to current directory)
+        /// Path to analyze, relative to the repository root (defaults to the whole repository)
         #[arg(default_value = ".")]
         path: String,
         /// Ownership percentage threshold (default: 80)
-        #[arg(short, long, default_value = "80.0")]
-        threshold: f64,
+        #[arg(short, long)]
+        threshold: Option<f64>,
+
+        /// How many directory levels below the path get their own bus factor
+        #[arg(long, default_value = "2")]
+        depth: usize,
+
+        /// Commit, branch or tag to analyze instead of HEAD
+        #[arg(long)]
+        rev: Option<String>,
+
+        /// Show what would be left mostly unowned if this author left: a whole name, email or
+        /// "Name <email>", or a regex matching one. Repeat for several people.
+        #[arg(long, value_name = "AUTHOR")]
+        simulate_leave: Vec<String>,
+
+        /// With --simulate-leave, the percentage of lines that must still be written by someone who stays
+        #[arg(long, value_name = "PERCENT", default_value = "50")]
+        coverage: f64,
     },
 }
 
+#[derive(Debug)]
+pub struct StandupConfig {
+    pub backend: Backend,
+    pub endpoint: String,
+    pub model: String,
+    pub diffstat: bool,
+}
+
+#[derive(Debug)]
+pub struct CouplingConfig {
+    pub path: Option<String>,
+    pub options: CouplingOptions,
+}
+
 #[derive(Debug)]
 pub struct Config {
+    /// Repository used by the analysis subcommands
     pub repo_path: String,
-    pub author: String,
+    /// Repositories explicitly passed with --repo-path
+    pub repo_paths: Vec<String>,
+    pub workspace: Option<String>,
+    pub authors: Vec<String>,
     pub since: String,
+    pub until: Option<String>,
+    pub calendar: Calendar,
     pub show_diff: bool,
     pub play: bool,
     pub save_music_path: Option<String>,
     pub is_hotspots_command: bool,
+    pub is_team_command: bool,
+    pub standup: Option<StandupConfig>,
+    pub coupling: Option<CouplingConfig>,
     pub hotspots_path: Option<String>,
+    pub hotspots_sort: HotspotSort,
+    pub hotspots_top: Option<usize>,
+    pub hotspots_rules: Vec<SuggestionRule>,
+    pub hotspots_grouping: HotspotGrouping,
+    pub hotspots_depth: usize,
+    /// Trend period and number of windows
+    pub hotspots_trend: Option<(TrendPeriod, usize)>,
     pub who_knows_path: Option<String>,
     pub bus_factor_path: Option<String>,
-    pub bus_factor_threshold: Option<f64>,
+    pub bus_factor_threshold: f64,
+    pub bus_factor_depth: usize,
+    pub bus_factor_rev: Option<String>,
+    pub bus_factor_leaving: Vec<String>,
+    pub bus_factor_coverage: f64,
+    /// Include/exclude globs shared by the analysis subcommands
+    pub paths: PathPatterns,
+    pub music: MusicConfig,
+    pub format: OutputFormat,
 }
 
-pub fn parse_cli_args() -> Config {
+/// Merges command-line flags over `.recap.toml`, over the user configuration,
+/// over the built-in defaults
+pub fn parse_cli_args() -> Result<Config, String> {
     let cli = Cli::parse();
-    let author = cli.author.unwrap_or_else(get_git_user_name);
+    let file = ConfigFile::load(cli.repo_path.first().map(String::as_str).unwrap_or("."))?;
+
+    let authors = if !cli.author.is_empty() {
+        cli.author
+    } else if let Some(authors) = file.authors.clone() {
+        authors
+    } else {
+        vec![get_git_user_name(cli.repo_path.first().map(String::as_str).unwrap_or("."))]
+    };
+
+    let mut repo_paths = cli.repo_path;
+    if repo_paths.is_empty() && cli.workspace.is_none() {
+        repo_paths.push(".".to_string());
+    }
 
-    let (is_hotspots_command, hotspots_path, who_knows_path, bus_factor_path, bus_factor_threshold, since) = match cli.command {
-        Some(Commands::Hotspots { path, since }) => (true, path, None, None, None, since),
-        Some(Commands::WhoKnows { path }) => (false, None, Some(path), None, None, cli.since),
-        Some(Commands::BusFactor { path, threshold }) => (false, None, None, Some(path), Some(threshold), cli.since),
-        None => (false, None, None, None, None, cli.since),
+    let work_days = if cli.work_days.is_empty() {
+        file.work_days.clone().unwrap_or_else(|| WorkWeek::default().days)
+    } else {
+        cli.work_days
     };
 
-    Config {
-        repo_path: cli.repo_path,
-        author,
-        since,
+    let mut holidays = file.holidays.clone();
+    holidays.extend(cli.holidays);
+
+    let default_music = MusicConfig::default();
+
+    let mut config = Config {
+        repo_path: repo_paths.first().cloned().unwrap_or_else(|| ".".to_string()),
+        repo_paths,
+        workspace: cli.workspace,
+        authors: file.expand_aliases(authors),
+        since: cli.since
+            .or_else(|| file.since.clone())
+            .unwrap_or_else(|| "last-standup".to_string()),
+        until: cli.until,
+        calendar: Calendar {
+            sprints: SprintCalendar {
+                anchor: cli.sprint_start,
+                length_days: cli.sprint_length,
+            },
+            work_week: WorkWeek {
+                days: work_days,
+                holidays,
+            },
+        },
         show_diff: cli.show_diff,
         play: cli.play,
         save_music_path: cli.save_music_path,
-        is_hotspots_command,
-        hotspots_path,
-        who_knows_path,
-        bus_factor_path,
-        bus_factor_threshold,
+        is_hotspots_command: false,
+        is_team_command: false,
+        standup: None,
+        coupling: None,
+        hotspots_path: None,
+        hotspots_sort: HotspotSort::Risk,
+        hotspots_top: file.hotspots.top,
+        hotspots_rules: file.hotspots.rules.clone().unwrap_or_else(default_rules),
+        hotspots_grouping: HotspotGrouping::File,
+        hotspots_depth: 2,
+        hotspots_trend: None,
+        who_knows_path: None,
+        bus_factor_path: None,
+        bus_factor_threshold: file.bus_factor.threshold.unwrap_or(80.0),
+        bus_factor_depth: 2,
+        bus_factor_rev: None,
+        bus_factor_leaving: Vec::new(),
+        bus_factor_coverage: 50.0,
+        paths: PathPatterns {
+            include: file.paths.include.iter().cloned().chain(cli.include).collect(),
+            exclude: file.paths.exclude.iter().cloned().chain(cli.exclude.iter().cloned()).collect(),
+        },
+        music: MusicConfig {
+            base_note: file.music.base_note.unwrap_or(default_music.base_note),
+            velocity: file.music.velocity.unwrap_or(default_music.velocity),
+            tempo: file.music.tempo.unwrap_or(default_music.tempo),
+        },
+        format: cli.format.or(file.format).unwrap_or(OutputFormat::Text),
+    };
+
+    // Hotspots and coupling also skip `[hotspots] ignore`, ahead of the
+    // command-line excludes so those can still override it
+    let hotspot_excludes: Vec<String> = file.paths.exclude.iter()
+        .chain(&file.hotspots.ignore)
+        .chain(&cli.exclude)
+        .cloned()
+        .collect();
+
+    match cli.command {
+        Some(Commands::Hotspots { path, since, sort_by, top, group_by, depth, trend, windows }) => {
+            config.is_hotspots_command = true;
+            config.hotspots_path = path;
+            config.hotspots_sort = sort_by;
+            config.hotspots_top = top.or(config.hotspots_top);
+            config.hotspots_grouping = group_by;
+            config.hotspots_depth = depth;
+            config.hotspots_trend = trend.map(|period| (period, windows));
+            config.paths.exclude = hotspot_excludes;
+            config.since = since;
+        }
+        Some(Commands::Coupling { path, since, min_shared, max_files }) => {
+            config.coupling = Some(CouplingConfig {
+                path,
+                options: CouplingOptions {
+                    min_shared_commits: min_shared,
+                    max_commit_files: max_files,
+                },
+            });
+            config.since = since;
+            config.paths.exclude = hotspot_excludes;
+        }
+        Some(Commands::Team { since }) => {
+            config.is_team_command = true;
+            if let Some(since) = since {
+                config.since = since;
+            }
+        }
+        Some(Commands::Standup { since, diffstat, backend, endpoint, model }) => {
+            config.standup = Some(StandupConfig { backend, endpoint, model, diffstat });
+            if let Some(since) = since {
+                config.since = since;
+            }
+        }
+        Some(Commands::WhoKnows { path, since }) => {
+            config.who_knows_path = Some(path);
+            config.since = since;
+        }
+        Some(Commands::BusFactor { path, threshold, depth, rev, simulate_leave, coverage }) => {
+            config.bus_factor_path = Some(path);
+            config.bus_factor_depth = depth;
+            config.bus_factor_rev = rev;
+            config.bus_factor_leaving = file.expand_aliases(simulate_leave);
+            config.bus_factor_coverage = coverage;
+            if let Some(threshold) = threshold {
+                config.bus_factor_threshold = threshold;
+            }
+        }
+        None => {}
     }
+
+    Ok(config)
 }
 
-fn get_git_user_name() -> String {
-    use std::process::Command;
-    
-    let output = Command::new("git")
-        .arg("config")
-        .arg("user.name")
-        .output()
-        .expect("Failed to execute git command");
-
-    if output.status.success() {
-        String::from_utf8_lossy(&output.stdout).trim().to_string()
-    } else {
-        panic!("Failed to get git user.name");
+fn parse_weekday(value: &str) -> Result<Weekday, String> {
+    value
+        .parse()
+        .map_err(|_| format!("'{}' is not a day of the week (use mon, tue, ...)", value))
+}
+
+fn get_git_user_name(repo_path: &str) -> String {
+    // Prefer the repository's own config, which layers over the global one
+    let config = git2::Repository::discover(repo_path)
+        .and_then(|repo| repo.config())
+        .or_else(|_| git2::Config::open_default());
+
+    config
+        .and_then(|config| config.get_string("user.name"))
+        .expect("Failed to get git user.name")
+}
+
+impl Config {
+    /// Human-readable description of the requested window, for report headers
+    pub fn period_label(&self) -> String {
+        match &self.until {
+            Some(until) => format!("{} until {}", self.since, until),
+            None => self.since.clone(),
+        }
     }
-} 
\ No newline at end of file
+}
@@ -0,0 +1,143 @@
+use chrono::{NaiveDate, Weekday};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use crate::hotspots::SuggestionRule;
+use crate::output::OutputFormat;
+
+/// Name of the repository-local configuration file, looked up at the repository root
+const REPO_CONFIG_FILE: &str = ".recap.toml";
+
+/// Settings read from `~/.config/recap/config.toml` and the repository's
+/// `.recap.toml`. Everything is optional: a missing value falls through to
+/// the next layer, and command-line flags always win.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigFile {
+    /// Default `--since` for the recap, `team` and `standup`
+    pub since: Option<String>,
+    pub format: Option<OutputFormat>,
+    /// Authors recapped when no `--author` is given
+    pub authors: Option<Vec<String>>,
+    /// Short names that expand to several author patterns
+    pub aliases: HashMap<String, Vec<String>>,
+    pub work_days: Option<Vec<Weekday>>,
+    pub holidays: Vec<NaiveDate>,
+    pub paths: PathsSection,
+    pub hotspots: HotspotsSection,
+    pub bus_factor: BusFactorSection,
+    pub music: MusicSection,
+}
+
+/// Gitignore-style globs applied by every analyzer
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PathsSection {
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HotspotsSection {
+    /// Gitignore-style globs skipped by the hotspots analysis only
+    pub ignore: Vec<String>,
+    /// Files listed in the text report
+    pub top: Option<usize>,
+    /// Replace the built-in suggestions
+    pub rules: Option<Vec<SuggestionRule>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BusFactorSection {
+    pub threshold: Option<f64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MusicSection {
+    pub tempo: Option<u32>,
+    pub base_note: Option<u8>,
+    pub velocity: Option<u8>,
+}
+
+impl ConfigFile {
+    /// Reads the user configuration, then the configuration of the
+    /// repository containing `repo_path` on top of it
+    pub fn load(repo_path: &str) -> Result<Self, String> {
+        let mut config = Self::default();
+        if let Some(path) = user_config_path() {
+            config = config.layer(Self::read(&path)?);
+        }
+        Ok(config.layer(Self::read(&repo_config_path(repo_path))?))
+    }
+
+    fn read(path: &Path) -> Result<Self, String> {
+        if !path.is_file() {
+            return Ok(Self::default());
+        }
+
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Error reading {}: {}", path.display(), e))?;
+        let config: Self = toml::from_str(&contents).map_err(|e| format!("Error parsing {}: {}", path.display(), e))?;
+        if config.music.tempo == Some(0) {
+            return Err(format!("Error in {}: [music] tempo must be at least 1 BPM", path.display()));
+        }
+        Ok(config)
+    }
+
+    /// Values set in `over` replace ours; lists and aliases are combined
+    fn layer(mut self, over: Self) -> Self {
+        self.aliases.extend(over.aliases);
+        self.holidays.extend(over.holidays);
+        self.paths.include.extend(over.paths.include);
+        self.paths.exclude.extend(over.paths.exclude);
+        self.hotspots.ignore.extend(over.hotspots.ignore);
+        self.hotspots.top = over.hotspots.top.or(self.hotspots.top);
+        self.hotspots.rules = over.hotspots.rules.or(self.hotspots.rules);
+
+        Self {
+            since: over.since.or(self.since),
+            format: over.format.or(self.format),
+            authors: over.authors.or(self.authors),
+            aliases: self.aliases,
+            work_days: over.work_days.or(self.work_days),
+            holidays: self.holidays,
+            paths: self.paths,
+            hotspots: self.hotspots,
+            bus_factor: BusFactorSection {
+                threshold: over.bus_factor.threshold.or(self.bus_factor.threshold),
+            },
+            music: MusicSection {
+                tempo: over.music.tempo.or(self.music.tempo),
+                base_note: over.music.base_note.or(self.music.base_note),
+                velocity: over.music.velocity.or(self.music.velocity),
+            },
+        }
+    }
+
+    /// Replaces every author that names an alias with the patterns it stands for
+    pub fn expand_aliases(&self, authors: Vec<String>) -> Vec<String> {
+        authors
+            .into_iter()
+            .flat_map(|author| self.aliases.get(&author).cloned().unwrap_or_else(|| vec![author]))
+            .collect()
+    }
+}
+
+fn user_config_path() -> Option<PathBuf> {
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_home.join("recap").join("config.toml"))
+}
+
+fn repo_config_path(repo_path: &str) -> PathBuf {
+    git2::Repository::discover(repo_path)
+        .ok()
+        .and_then(|repo| repo.workdir().map(Path::to_path_buf))
+        .unwrap_or_else(|| PathBuf::from(repo_path))
+        .join(REPO_CONFIG_FILE)
+}
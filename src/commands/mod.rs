@@ -1,2 +1,3 @@
 pub mod cli;
+mod config_file;
 pub use cli::parse_cli_args; 
\ No newline at end of file
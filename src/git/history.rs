@@ -0,0 +1,121 @@
+use std::path::Path;
+use std::time::Duration;
+use git2::{Commit, Delta, DiffFindOptions, Oid, Patch, Repository};
+use chrono::{DateTime, Utc};
+use indicatif::{ProgressBar, ProgressStyle};
+
+/// A commit's changed files, as diffed by a worker thread
+pub struct CommitChanges {
+    pub time: DateTime<Utc>,
+    pub name: String,
+    pub email: String,
+    pub files: Vec<ChangedFile>,
+}
+
+pub struct ChangedFile {
+    /// Set when the file was renamed in this commit
+    pub old_path: Option<String>,
+    pub path: String,
+    pub deleted: bool,
+    pub additions: u32,
+    pub deletions: u32,
+}
+
+/// Diffs every commit in `oids` against its first parent on all cores, with
+/// a progress bar. The changes come back in the order of `oids`.
+pub fn diff_history(repo: &Repository, oids: &[Oid]) -> Result<Vec<CommitChanges>, git2::Error> {
+    // Setup progress bar, advanced by the workers as they diff commits
+    let progress_bar = ProgressBar::new(oids.len() as u64);
+    progress_bar.set_style(
+        ProgressStyle::default_bar()
+            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} commits ({per_sec})")
+            .unwrap()
+            .progress_chars("#>-")
+    );
+    progress_bar.enable_steady_tick(Duration::from_millis(100));
+
+    // Each worker opens its own handle on the repository and diffs a
+    // contiguous slice of the history, so results come back in order
+    let workers = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    let chunk_size = oids.len().div_ceil(workers).max(1);
+    let repo_path = repo.path();
+    let chunks: Vec<Result<Vec<CommitChanges>, git2::Error>> = std::thread::scope(|scope| {
+        let handles: Vec<_> = oids
+            .chunks(chunk_size)
+            .map(|chunk| {
+                let progress_bar = &progress_bar;
+                scope.spawn(move || diff_commits(repo_path, chunk, progress_bar))
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("Diff worker panicked"))
+            .collect()
+    });
+    progress_bar.finish_and_clear();
+
+    let mut commits = Vec::with_capacity(oids.len());
+    for chunk in chunks {
+        commits.extend(chunk?);
+    }
+    Ok(commits)
+}
+
+fn diff_commits(repo_path: &Path, oids: &[Oid], progress_bar: &ProgressBar) -> Result<Vec<CommitChanges>, git2::Error> {
+    let repo = Repository::open(repo_path)?;
+    oids.iter()
+        .map(|oid| {
+            let changes = diff_commit(&repo, &repo.find_commit(*oid)?)?;
+            progress_bar.inc(1);
+            Ok(changes)
+        })
+        .collect()
+}
+
+/// The equivalent of `git log --numstat --find-renames` for one commit. The
+/// whole tree is diffed, even with a path filter, so files moved into the
+/// filtered directory are seen as renames rather than additions.
+fn diff_commit(repo: &Repository, commit: &Commit) -> Result<CommitChanges, git2::Error> {
+    let tree = commit.tree()?;
+    let parent_tree = match commit.parent(0) {
+        Ok(parent) => Some(parent.tree()?),
+        Err(_) => None,
+    };
+
+    let mut diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
+    diff.find_similar(Some(DiffFindOptions::new().renames(true)))?;
+
+    let mut files = Vec::new();
+    for (idx, delta) in diff.deltas().enumerate() {
+        let path_of = |file: git2::DiffFile| file.path().map(|p| p.to_string_lossy().replace('\\', "/"));
+        let Some(path) = path_of(delta.new_file()).or_else(|| path_of(delta.old_file())) else {
+            continue;
+        };
+        let old_path = if delta.status() == Delta::Renamed {
+            path_of(delta.old_file())
+        } else {
+            None
+        };
+        let deleted = delta.status() == Delta::Deleted;
+
+        // Binary files have no patch and count as zero lines, like numstat's "-"
+        let (additions, deletions) = match Patch::from_diff(&diff, idx)? {
+            Some(patch) => {
+                let (_, additions, deletions) = patch.line_stats()?;
+                (additions as u32, deletions as u32)
+            }
+            None => (0, 0),
+        };
+
+        files.push(ChangedFile { old_path, path, deleted, additions, deletions });
+    }
+
+    let author = commit.author();
+    Ok(CommitChanges {
+        // Commit time, the same the window is filtered on
+        time: DateTime::<Utc>::from_timestamp(commit.time().seconds(), 0).unwrap_or_default(),
+        name: author.name().unwrap_or("Unknown").to_string(),
+        email: author.email().unwrap_or("").to_string(),
+        files,
+    })
+}
@@ -0,0 +1,85 @@
+use git2::{Mailmap, Repository, Signature, Time};
+use regex::Regex;
+
+/// Maps the many names and emails a person commits under to one canonical
+/// name, using the repository's `.mailmap` (and `mailmap.file`/`mailmap.blob`).
+pub struct AuthorResolver {
+    mailmap: Option<Mailmap>,
+}
+
+impl AuthorResolver {
+    pub fn new(repo: &Repository) -> Self {
+        Self { mailmap: repo.mailmap().ok() }
+    }
+
+    /// Opens the repository containing `path` and builds its resolver; falls
+    /// back to raw names when the path is not inside a repository.
+    pub fn discover(path: &str) -> Self {
+        match Repository::discover(path) {
+            Ok(repo) => Self::new(&repo),
+            Err(_) => Self { mailmap: None },
+        }
+    }
+
+    pub fn resolve(&self, name: &str, email: &str) -> String {
+        self.resolve_identity(name, email).0
+    }
+
+    /// Returns the canonical `(name, email)` pair for an author
+    pub fn resolve_identity(&self, name: &str, email: &str) -> (String, String) {
+        let resolved = self.mailmap.as_ref().and_then(|mailmap| {
+            let signature = Signature::new(name, email, &Time::new(0, 0)).ok()?;
+            mailmap.resolve_signature(&signature).ok()
+        });
+
+        match resolved {
+            Some(signature) => (
+                signature.name().unwrap_or(name).to_string(),
+                signature.email().unwrap_or(email).to_string(),
+            ),
+            None => (name.to_string(), email.to_string()),
+        }
+    }
+}
+
+/// Matches authors against one or more `--author` patterns. Like
+/// `git log --author`, each pattern is a regex tested against "Name <email>".
+/// An empty filter matches everyone.
+pub struct AuthorFilter {
+    patterns: Vec<Regex>,
+}
+
+impl AuthorFilter {
+    pub fn new(authors: &[String]) -> Self {
+        let patterns = authors
+            .iter()
+            .map(|author| {
+                Regex::new(author)
+                    .unwrap_or_else(|_| Regex::new(&regex::escape(author)).expect("escaped regex is valid"))
+            })
+            .collect();
+        Self { patterns }
+    }
+
+    /// Like `new`, but each pattern has to match a whole name, a whole email
+    /// or the whole "Name <email>", so "Al" does not pick up "Alan"
+    pub fn exact(authors: &[String]) -> Self {
+        let patterns = authors
+            .iter()
+            .map(|author| {
+                Regex::new(&format!("^(?:{})$", author))
+                    .unwrap_or_else(|_| Regex::new(&format!("^{}$", regex::escape(author))).expect("escaped regex is valid"))
+            })
+            .collect();
+        Self { patterns }
+    }
+
+    /// True if any pattern matches any of the given "Name <email>" identities
+    pub fn matches(&self, identities: &[&str]) -> bool {
+        self.patterns.is_empty()
+            || self
+                .patterns
+                .iter()
+                .any(|pattern| identities.iter().any(|identity| pattern.is_match(identity)))
+    }
+}
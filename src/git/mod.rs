@@ -1,10 +1,20 @@
+mod history;
+mod identity;
 mod operations;
+mod team;
+mod workspace;
 
 pub use operations::{
     GitCommit,
     GitStats,
     validate_repo,
     get_commits,
-    get_commit_diff,
-    get_stats,
-}; 
\ No newline at end of file
+    walk_all_refs,
+};
+// Tests elsewhere build commits by hand
+#[cfg(test)]
+pub use operations::FileChange;
+pub use history::diff_history;
+pub use identity::{AuthorFilter, AuthorResolver};
+pub use team::{AuthorRecap, group_by_author};
+pub use workspace::{RepoRecap, find_repositories};
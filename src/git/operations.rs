@@ -1,140 +1,305 @@
-use regex::Regex;
-use std::io::{self, BufRead};
+use chrono::{DateTime, Utc};
+use colored::*;
+use git2::{Commit, Diff, DiffFormat, DiffStatsFormat, Oid, Patch, Repository, Sort};
+use serde::Serialize;
 use std::path::Path;
-use std::process::{Command, Stdio};
+use crate::git::identity::{AuthorFilter, AuthorResolver};
+use crate::period::DateRange;
 
+#[derive(Serialize)]
 pub struct GitCommit {
     pub hash: String,
     pub message: String,
+    #[serde(skip)]
     pub relative_time: String,
     pub author: String,
+    pub timestamp: DateTime<Utc>,
+    pub files: Vec<FileChange>,
+    /// Colored `git show`-style patch, only collected when diffs are requested
+    #[serde(skip)]
+    pub patch: Option<String>,
 }
 
+#[derive(Serialize)]
+pub struct FileChange {
+    pub path: String,
+    pub additions: i32,
+    pub deletions: i32,
+    /// numstat's "-": no line counts
+    #[serde(skip)]
+    pub binary: bool,
+}
+
+#[derive(Serialize)]
 pub struct GitStats {
     pub commits_count: i32,
     pub total_additions: i32,
     pub total_deletions: i32,
 }
 
+impl GitCommit {
+    pub fn short_hash(&self) -> &str {
+        &self.hash[..self.hash.len().min(11)]
+    }
+}
+
+impl GitStats {
+    pub fn from_commits(commits: &[GitCommit]) -> Self {
+        let mut stats = GitStats {
+            commits_count: 0,
+            total_additions: 0,
+            total_deletions: 0,
+        };
+
+        for commit in commits {
+            stats.commits_count += 1;
+            for file in &commit.files {
+                stats.total_additions += file.additions;
+                stats.total_deletions += file.deletions;
+            }
+        }
+
+        stats
+    }
+
+    pub fn combine<'a>(stats: impl IntoIterator<Item = &'a GitStats>) -> Self {
+        let mut total = GitStats {
+            commits_count: 0,
+            total_additions: 0,
+            total_deletions: 0,
+        };
+
+        for s in stats {
+            total.commits_count += s.commits_count;
+            total.total_additions += s.total_additions;
+            total.total_deletions += s.total_deletions;
+        }
+
+        total
+    }
+}
+
 pub fn validate_repo(repo_path: &str) -> Result<(), String> {
     if !Path::new(repo_path).is_dir() {
         return Err(format!("Error: '{repo_path}' is not a valid directory."));
     }
 
-    let output = Command::new("git")
-        .arg("-C")
-        .arg(repo_path)
-        .arg("rev-parse")
-        .arg("--is-inside-work-tree")
-        .output()
-        .map_err(|e| format!("Error running `git rev-parse`: {e}"))?;
-
-    if !output.status.success() || String::from_utf8_lossy(&output.stdout).trim() != "true" {
-        return Err(format!("Error: '{repo_path}' is not a Git repository."));
+    match Repository::discover(repo_path) {
+        Ok(repo) if !repo.is_bare() => Ok(()),
+        _ => Err(format!("Error: '{repo_path}' is not a Git repository.")),
     }
-
-    Ok(())
 }
 
-pub fn get_commits(repo_path: &str, author: &str, since: &str, show_diff: bool) -> Vec<GitCommit> {
-    let format = if show_diff {
-        "%h - %s [%cr by %an]%n"
-    } else {
-        "%h - %s [%cr by %an]"
-    };
+/// Walks the history once, collecting each matching commit with its per-file
+/// changes and, when `show_diff` is set, its rendered patch.
+pub fn get_commits(repo_path: &str, authors: &[String], range: &DateRange, show_diff: bool) -> Result<Vec<GitCommit>, String> {
+    let repo = Repository::discover(repo_path).map_err(|e| format!("Error opening repository: {e}"))?;
+    let resolver = AuthorResolver::new(&repo);
+    let author_filter = AuthorFilter::new(authors);
 
-    let mut child = Command::new("git")
-        .arg("-C")
-        .arg(repo_path)
-        .arg("--no-pager")
-        .arg("log")
-        .arg("--all")
-        .arg(format!("--author={}", author))
-        .arg(format!("--since={}", since))
-        .arg(format!("--pretty=format:{}", format))
-        .stdout(Stdio::piped())
-        .spawn()
-        .expect("Failed to run git log");
-
-    let re = Regex::new(r"^([0-9a-f]+) - (.*?) \[(.*?) by (.*?)\]$").unwrap();
     let mut commits = Vec::new();
-
-    if let Some(stdout) = child.stdout.take() {
-        let reader = io::BufReader::new(stdout);
-        for line in reader.lines() {
-            if let Ok(line_str) = line {
-                if let Some(caps) = re.captures(&line_str) {
-                    commits.push(GitCommit {
-                        hash: caps.get(1).unwrap().as_str().to_string(),
-                        message: caps.get(2).unwrap().as_str().to_string(),
-                        relative_time: caps.get(3).unwrap().as_str().to_string(),
-                        author: caps.get(4).unwrap().as_str().to_string(),
-                    });
-                }
-            }
+    for oid in walk_all_refs(&repo, Sort::TIME).map_err(|e| format!("Error walking history: {e}"))? {
+        let commit = repo.find_commit(oid).map_err(|e| format!("Error reading commit {oid}: {e}"))?;
+        if !matches_filters(&commit, &resolver, &author_filter, range) {
+            continue;
         }
+        commits.push(to_git_commit(&repo, &resolver, &commit, show_diff).map_err(|e| format!("Error diffing commit {oid}: {e}"))?);
+    }
+
+    Ok(commits)
+}
+
+/// Mirrors `git log --all`: every ref plus HEAD, in `sorting` order
+pub fn walk_all_refs(repo: &Repository, sorting: Sort) -> Result<Vec<Oid>, git2::Error> {
+    let mut revwalk = repo.revwalk()?;
+    revwalk.set_sorting(sorting)?;
+    // An unborn HEAD simply has nothing to walk
+    let _ = revwalk.push_head();
+    revwalk.push_glob("*")?;
+    revwalk.collect()
+}
+
+fn matches_filters(
+    commit: &Commit,
+    resolver: &AuthorResolver,
+    author_filter: &AuthorFilter,
+    range: &DateRange,
+) -> bool {
+    if !range.contains(commit_time(commit)) {
+        return false;
     }
 
-    commits
+    // Match both the recorded identity and its mailmapped form, so any alias finds the person
+    let author = commit.author();
+    let (name, email) = (author.name().unwrap_or(""), author.email().unwrap_or(""));
+    let (canonical_name, canonical_email) = resolver.resolve_identity(name, email);
+    author_filter.matches(&[
+        &format!("{} <{}>", name, email),
+        &format!("{} <{}>", canonical_name, canonical_email),
+    ])
 }
 
-pub fn get_commit_diff(repo_path: &str, commit_hash: &str) -> Option<String> {
-    let output = Command::new("git")
-        .arg("-C")
-        .arg(repo_path)
-        .arg("--no-pager")
-        .arg("show")
-        .arg("--color=always")
-        .arg("--stat")
-        .arg("--patch")
-        .arg(commit_hash)
-        .output()
-        .ok()?;
-
-    if output.status.success() {
-        Some(String::from_utf8_lossy(&output.stdout).to_string())
+fn to_git_commit(repo: &Repository, resolver: &AuthorResolver, commit: &Commit, show_diff: bool) -> Result<GitCommit, git2::Error> {
+    let timestamp = commit_time(commit);
+
+    // Like `git log --numstat --patch`, merges carry no file list and no patch:
+    // against the first parent they would repeat everything merged in
+    let diff = if commit.parent_count() > 1 {
+        None
+    } else {
+        Some(diff_to_parent(repo, commit)?)
+    };
+    let files = match &diff {
+        Some(diff) => numstat(diff)?,
+        None => Vec::new(),
+    };
+    let patch = if show_diff {
+        Some(format_patch(commit, diff.as_ref())?)
     } else {
         None
+    };
+
+    Ok(GitCommit {
+        hash: commit.id().to_string(),
+        message: commit.summary().unwrap_or("").to_string(),
+        relative_time: format_relative_time(timestamp, Utc::now()),
+        author: resolver.resolve(commit.author().name().unwrap_or("Unknown"), commit.author().email().unwrap_or("")),
+        timestamp,
+        files,
+        patch,
+    })
+}
+
+/// Renders a commit the way `git log --stat --patch --color=always` does.
+/// Merges, which have no `diff`, get their header and message only.
+fn format_patch(commit: &Commit, diff: Option<&Diff>) -> Result<String, git2::Error> {
+    let author = commit.author();
+    let merge = if commit.parent_count() > 1 {
+        let parents: Vec<String> = commit.parent_ids().map(|id| id.to_string()[..7].to_string()).collect();
+        format!("Merge: {}\n", parents.join(" "))
+    } else {
+        String::new()
+    };
+    let mut output = format!(
+        "{}\n{}Author: {} <{}>\nDate:   {}\n\n",
+        format!("commit {}", commit.id()).yellow(),
+        merge,
+        author.name().unwrap_or("Unknown"),
+        author.email().unwrap_or(""),
+        commit_time(commit).to_rfc2822()
+    );
+    for line in commit.message().unwrap_or("").lines() {
+        output.push_str(&format!("    {}\n", line));
     }
+    output.push('\n');
+
+    let Some(diff) = diff else {
+        return Ok(output);
+    };
+    let stats = diff.stats()?.to_buf(DiffStatsFormat::FULL, 80)?;
+    output.push_str(&String::from_utf8_lossy(&stats));
+    output.push('\n');
+
+    diff.print(DiffFormat::Patch, |_, _, line| {
+        let content = String::from_utf8_lossy(line.content());
+        let text = match line.origin() {
+            origin @ ('+' | '-' | ' ') => format!("{}{}", origin, content),
+            _ => content.to_string(),
+        };
+        let colored_text = match line.origin() {
+            '+' => text.green().to_string(),
+            '-' => text.red().to_string(),
+            'F' => text.bold().to_string(),
+            'H' => text.cyan().to_string(),
+            _ => text,
+        };
+        output.push_str(&colored_text);
+        true
+    })?;
+
+    Ok(output)
+}
+
+fn commit_time(commit: &Commit) -> DateTime<Utc> {
+    DateTime::<Utc>::from_timestamp(commit.time().seconds(), 0).unwrap_or_default()
 }
 
-pub fn get_stats(repo_path: &str, author: &str, since: &str) -> GitStats {
-    let mut stats = GitStats {
-        commits_count: 0,
-        total_additions: 0,
-        total_deletions: 0,
+fn diff_to_parent<'r>(repo: &'r Repository, commit: &Commit) -> Result<Diff<'r>, git2::Error> {
+    let tree = commit.tree()?;
+    let parent_tree = match commit.parent(0) {
+        Ok(parent) => Some(parent.tree()?),
+        Err(_) => None,
     };
 
-    let mut child = Command::new("git")
-        .arg("-C")
-        .arg(repo_path)
-        .arg("log")
-        .arg("--all")
-        .arg(format!("--author={}", author))
-        .arg(format!("--since={}", since))
-        .arg("--pretty=tformat:COMMIT")
-        .arg("--numstat")
-        .stdout(Stdio::piped())
-        .spawn()
-        .expect("Failed to run git log for stats");
-
-    if let Some(stdout) = child.stdout.take() {
-        let reader = io::BufReader::new(stdout);
-        for line in reader.lines().flatten() {
-            let line_str = line.trim().to_string();
-            if line_str == "COMMIT" {
-                stats.commits_count += 1;
-            } else if !line_str.is_empty() {
-                let parts: Vec<&str> = line_str.split_whitespace().collect();
-                if parts.len() >= 3 {
-                    let added = parts[0].parse::<i32>().unwrap_or(0);
-                    let deleted = parts[1].parse::<i32>().unwrap_or(0);
-                    stats.total_additions += added;
-                    stats.total_deletions += deleted;
-                }
+    let mut diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
+    diff.find_similar(None)?;
+    Ok(diff)
+}
+
+fn numstat(diff: &Diff) -> Result<Vec<FileChange>, git2::Error> {
+    let mut files = Vec::new();
+
+    for (idx, delta) in diff.deltas().enumerate() {
+        let path = delta
+            .new_file()
+            .path()
+            .or_else(|| delta.old_file().path())
+            .map(|p| p.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        // Binary files have no patch and count as zero lines, like numstat's "-"
+        let (additions, deletions, binary) = match Patch::from_diff(diff, idx)? {
+            Some(patch) => {
+                let (_, additions, deletions) = patch.line_stats()?;
+                (additions as i32, deletions as i32, false)
             }
-        }
+            None => (0, 0, true),
+        };
+
+        files.push(FileChange { path, additions, deletions, binary });
+    }
+
+    Ok(files)
+}
+
+/// Formats a timestamp the way `git log --format=%cr` does
+fn format_relative_time(timestamp: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let seconds = (now - timestamp).num_seconds();
+    if seconds < 0 {
+        return "in the future".to_string();
     }
 
-    stats
-} 
\ No newline at end of file
+    let plural = |n: i64, unit: &str| format!("{} {}{} ago", n, unit, if n == 1 { "" } else { "s" });
+
+    if seconds < 90 {
+        return plural(seconds, "second");
+    }
+    let minutes = (seconds + 30) / 60;
+    if minutes < 90 {
+        return plural(minutes, "minute");
+    }
+    let hours = (minutes + 30) / 60;
+    if hours < 36 {
+        return plural(hours, "hour");
+    }
+    let days = (hours + 12) / 24;
+    if days < 14 {
+        return plural(days, "day");
+    }
+    if days < 70 {
+        return plural((days + 3) / 7, "week");
+    }
+    if days < 365 {
+        return plural((days + 15) / 30, "month");
+    }
+    if days < 1825 {
+        let total_months = (days * 12 * 2 + 365) / (365 * 2);
+        let (years, months) = (total_months / 12, total_months % 12);
+        if months > 0 {
+            let years_part = format!("{} year{}", years, if years == 1 { "" } else { "s" });
+            return format!("{}, {} month{} ago", years_part, months, if months == 1 { "" } else { "s" });
+        }
+        return plural(years, "year");
+    }
+    plural((days + 183) / 365, "year")
+}
@@ -0,0 +1,32 @@
+use serde::Serialize;
+use crate::git::{GitCommit, GitStats};
+
+/// One author's share of a team recap
+#[derive(Serialize)]
+pub struct AuthorRecap {
+    pub author: String,
+    pub commits: Vec<GitCommit>,
+    pub stats: GitStats,
+}
+
+/// Groups commits by their (mailmapped) author, busiest author first
+pub fn group_by_author(commits: Vec<GitCommit>) -> Vec<AuthorRecap> {
+    let mut groups: Vec<(String, Vec<GitCommit>)> = Vec::new();
+
+    for commit in commits {
+        match groups.iter_mut().find(|(author, _)| *author == commit.author) {
+            Some((_, author_commits)) => author_commits.push(commit),
+            None => groups.push((commit.author.clone(), vec![commit])),
+        }
+    }
+
+    let mut recaps: Vec<AuthorRecap> = groups
+        .into_iter()
+        .map(|(author, commits)| {
+            let stats = GitStats::from_commits(&commits);
+            AuthorRecap { author, commits, stats }
+        })
+        .collect();
+    recaps.sort_by_key(|recap| std::cmp::Reverse(recap.stats.commits_count));
+    recaps
+}
@@ -0,0 +1,52 @@
+use serde::Serialize;
+use std::path::Path;
+use crate::git::{GitCommit, GitStats};
+
+/// The recap of a single repository inside a multi-repository run
+#[derive(Serialize)]
+pub struct RepoRecap {
+    pub repo_path: String,
+    pub commits: Vec<GitCommit>,
+    pub stats: GitStats,
+}
+
+/// Finds every git work tree below `dir`. Hidden directories and symlinks are
+/// skipped and the search does not descend into a repository once one is found.
+pub fn find_repositories(dir: &str) -> Result<Vec<String>, String> {
+    let root = Path::new(dir);
+    if !root.is_dir() {
+        return Err(format!("Error: '{dir}' is not a valid directory."));
+    }
+
+    let mut repos = Vec::new();
+    collect_repositories(root, &mut repos);
+    repos.sort();
+    Ok(repos)
+}
+
+fn collect_repositories(dir: &Path, repos: &mut Vec<String>) {
+    // `.git` is a directory in a regular clone and a file in worktrees and submodules
+    if dir.join(".git").exists() {
+        repos.push(dir.to_string_lossy().into_owned());
+        return;
+    }
+
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let hidden = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .map(|n| n.starts_with('.'))
+            .unwrap_or(false);
+
+        // Symlinks are not followed, so a link to a parent cannot loop forever
+        let is_dir = entry.file_type().map(|file_type| file_type.is_dir()).unwrap_or(false);
+        if is_dir && !hidden {
+            collect_repositories(&path, repos);
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use regex::Regex;
+use serde::Serialize;
+use std::path::Path;
+use crate::lexer::{LineClassifier, LineKind};
+
+/// Size and shape of a file in the working tree
+#[derive(Debug, Clone, Serialize)]
+pub struct Complexity {
+    /// Lines that are neither blank nor a comment on their own
+    pub lines_of_code: usize,
+    /// Sum of the logical indentation of every line of code
+    pub indentation: usize,
+    /// Deepest logical indentation
+    pub max_depth: usize,
+    pub functions: usize,
+}
+
+impl Complexity {
+    /// Measures a file from disk. `None` for files that are gone or not text.
+    pub fn measure(path: &Path) -> Option<Self> {
+        let content = std::fs::read_to_string(path).ok()?;
+        Some(Self::from_source(&content, path))
+    }
+
+    fn from_source(content: &str, path: &Path) -> Self {
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        let mut classifier = LineClassifier::for_path(path);
+        let code: Vec<&str> = content
+            .lines()
+            .filter(|line| classifier.classify(line) == LineKind::Code)
+            .collect();
+
+        let unit = indent_unit(&code);
+        let depths: Vec<usize> = code.iter().map(|line| depth(line, unit)).collect();
+
+        let functions = function_pattern(extension)
+            .map(|pattern| code.iter().filter(|line| pattern.is_match(line)).count())
+            .unwrap_or(0);
+
+        Self {
+            lines_of_code: code.len(),
+            indentation: depths.iter().sum(),
+            max_depth: depths.iter().copied().max().unwrap_or(0),
+            functions,
+        }
+    }
+
+    /// Average nesting of a line of code
+    pub fn mean_depth(&self) -> f64 {
+        if self.lines_of_code == 0 {
+            0.0
+        } else {
+            self.indentation as f64 / self.lines_of_code as f64
+        }
+    }
+}
+
+/// Width of one level of space indentation: the smallest indent used, within reason
+fn indent_unit(lines: &[&str]) -> usize {
+    lines
+        .iter()
+        .map(|line| line.len() - line.trim_start_matches(' ').len())
+        .filter(|&spaces| spaces > 0)
+        .min()
+        .unwrap_or(4)
+        .clamp(2, 8)
+}
+
+fn depth(line: &str, unit: usize) -> usize {
+    let mut tabs = 0;
+    let mut spaces = 0;
+    for c in line.chars() {
+        match c {
+            '\t' => tabs += 1,
+            ' ' => spaces += 1,
+            _ => break,
+        }
+    }
+    tabs + spaces / unit
+}
+
+/// A line-based function declaration pattern for common languages
+fn function_pattern(extension: &str) -> Option<Regex> {
+    let pattern = match extension {
+        "rs" => r"^\s*(pub(\([^)]*\))?\s+)?(const\s+)?(async\s+)?(unsafe\s+)?(extern\s+\S+\s+)?fn\s+\w+",
+        "py" => r"^\s*(async\s+)?def\s+\w+",
+        "rb" => r"^\s*def\s+\S+",
+        "go" => r"^func\s",
+        "js" | "jsx" | "ts" | "tsx" | "mjs" | "cjs" => {
+            r"\bfunction\b|^\s*(async\s+)?\w+\s*\([^)]*\)\s*\{|=>\s*\{?\s*$|=\s*(async\s+)?\([^)]*\)\s*=>"
+        }
+        "java" | "kt" | "cs" | "scala" | "swift" | "dart" => {
+            r"^\s*((public|private|protected|internal|static|final|override|abstract|open|suspend|async)\s+)*(fun|func|def|[\w<>\[\],?]+)\s+\w+\s*\([^;]*$"
+        }
+        "c" | "h" | "cc" | "cpp" | "cxx" | "hpp" | "m" => r"^[A-Za-z_][\w\s\*&:<>,]*\s[\*&]?\w[\w:]*\s*\([^;]*$",
+        "php" => r"\bfunction\s+\w+",
+        "ex" | "exs" => r"^\s*defp?\s+\w+",
+        "sh" | "bash" | "zsh" => r"^\s*(function\s+\w+|\w+\s*\(\)\s*\{)",
+        _ => return None,
+    };
+    Some(Regex::new(pattern).expect("valid regex"))
+}
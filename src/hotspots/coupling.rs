@@ -0,0 +1,117 @@
+use std::collections::{BTreeSet, HashMap};
+use colored::*;
+use serde::Serialize;
+use crate::hotspots::HotspotAnalyzer;
+use crate::period::DateRange;
+
+/// Pairs listed in the text report; the other formats get all of them
+const REPORTED_PAIRS: usize = 20;
+
+#[derive(Debug, Clone, Copy)]
+pub struct CouplingOptions {
+    /// Pairs changed together fewer times than this are noise
+    pub min_shared_commits: usize,
+    /// Commits touching more files than this (bulk reformats, renames) are ignored
+    pub max_commit_files: usize,
+}
+
+/// Two files that tend to change in the same commits
+#[derive(Debug, Serialize)]
+pub struct FileCoupling {
+    pub file: String,
+    pub coupled_with: String,
+    pub shared_commits: usize,
+    /// Commits touching `file`, among the ones considered
+    pub file_commits: usize,
+    pub coupled_commits: usize,
+    /// Shared commits as a percentage of the average commits of the two files
+    pub coupling: f64,
+}
+
+impl HotspotAnalyzer {
+    pub fn coupling(&self, range: &DateRange, options: CouplingOptions) -> Result<Vec<FileCoupling>, git2::Error> {
+        // The files of each commit in the analysis, with how many it changed in all
+        let mut commits: Vec<(usize, BTreeSet<String>)> = Vec::new();
+        let mut current_commit = None;
+        self.walk_history(range, |change| {
+            if current_commit != Some(change.commit) {
+                current_commit = Some(change.commit);
+                commits.push((change.commit_files, BTreeSet::new()));
+            }
+            if let Some((_, files)) = commits.last_mut() {
+                files.insert(change.path.to_string());
+            }
+        })?;
+
+        let mut revisions: HashMap<&str, usize> = HashMap::new();
+        let mut shared: HashMap<(&str, &str), usize> = HashMap::new();
+
+        // A bulk commit is skipped even if only a few of its files are analyzed
+        for (_, files) in commits.iter().filter(|(commit_files, _)| *commit_files <= options.max_commit_files) {
+            let files: Vec<&str> = files.iter().map(String::as_str).collect();
+            for (i, file) in files.iter().enumerate() {
+                *revisions.entry(file).or_insert(0) += 1;
+                // Sets iterate in order, so every pair has a single key
+                for other in &files[i + 1..] {
+                    *shared.entry((file, other)).or_insert(0) += 1;
+                }
+            }
+        }
+
+        let mut result: Vec<FileCoupling> = shared
+            .into_iter()
+            .filter(|(_, count)| *count >= options.min_shared_commits)
+            .map(|((file, other), count)| {
+                let file_commits = revisions[file];
+                let coupled_commits = revisions[other];
+                FileCoupling {
+                    file: file.to_string(),
+                    coupled_with: other.to_string(),
+                    shared_commits: count,
+                    file_commits,
+                    coupled_commits,
+                    coupling: count as f64 * 200.0 / (file_commits + coupled_commits) as f64,
+                }
+            })
+            .collect();
+
+        result.sort_by(|a, b| {
+            b.coupling
+                .total_cmp(&a.coupling)
+                .then(b.shared_commits.cmp(&a.shared_commits))
+                .then_with(|| a.file.cmp(&b.file))
+        });
+        Ok(result)
+    }
+}
+
+pub fn format_coupling_report(couplings: &[FileCoupling], since: &str) -> String {
+    if couplings.is_empty() {
+        return String::new();
+    }
+
+    let mut output = if since == "all" {
+        "Files That Change Together:\n\n".bold().to_string()
+    } else {
+        format!("Files That Change Together since {}:\n\n", since).bold().to_string()
+    };
+
+    for (i, coupling) in couplings.iter().enumerate().take(REPORTED_PAIRS) {
+        output.push_str(&format!(
+            "{}. {} {} {}\n",
+            (i + 1).to_string().blue(),
+            coupling.file.green(),
+            "<->".dimmed(),
+            coupling.coupled_with.green()
+        ));
+        output.push_str(&format!(
+            "   - Coupling: {} ({} shared commits, {} and {} in total)\n\n",
+            format!("{:.0}%", coupling.coupling).yellow(),
+            coupling.shared_commits.to_string().cyan(),
+            coupling.file_commits,
+            coupling.coupled_commits
+        ));
+    }
+
+    output
+}
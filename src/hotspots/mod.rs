@@ -1,28 +1,96 @@
-use std::collections::HashMap;
+mod complexity;
+mod coupling;
+mod rules;
+mod tree;
+mod trend;
+
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
-use std::time::Duration;
-use std::io::BufRead;
-use git2::{Repository, Commit, ObjectType, Time};
+use git2::{Repository, Sort};
 use chrono::{DateTime, Utc};
+use clap::ValueEnum;
 use colored::*;
-use indicatif::{ProgressBar, ProgressStyle};
+use serde::Serialize;
+use crate::git::{AuthorResolver, diff_history, walk_all_refs};
+use crate::path_filter::{PathFilter, PathPatterns};
+use crate::period::DateRange;
+
+pub use complexity::Complexity;
+pub use coupling::{CouplingOptions, FileCoupling, format_coupling_report};
+pub use rules::{SuggestionRule, default_rules};
+pub use trend::{TrendPeriod, TrendPoint, TrendWindows, sparkline};
+pub use tree::{DirectoryHotspot, HotspotGrouping, format_directory_report, group_by_directory, truncate_directories};
+
+// Churn from a commit this many days old counts half as much as today's
+const RECENCY_HALF_LIFE_DAYS: f64 = 90.0;
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct FileHotspot {
     pub path: String,
     pub commit_count: usize,
     pub contributor_count: usize,
+    pub lines_added: u32,
+    pub lines_removed: u32,
+    /// Recency-weighted churn scaled by the number of contributors, see `score_commit`
+    pub score: f64,
+    /// Measured on the working tree, `None` if the file can't be read
+    pub complexity: Option<Complexity>,
+    /// `score` scaled up by the log of the total indentation: churn in complex
+    /// code. Flat and unreadable files keep their plain score.
+    pub risk: f64,
     pub last_modified: DateTime<Utc>,
     pub contributors: HashMap<String, usize>,
+    /// Ordinals of the commits touching the file, so directories can count distinct commits
+    #[serde(skip)]
+    pub commit_ids: Vec<u64>,
+    /// Commits and churn per window, oldest first, when a trend was requested
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub trend: Vec<TrendPoint>,
+}
+
+impl FileHotspot {
+    fn new(path: &str, last_modified: DateTime<Utc>) -> Self {
+        Self {
+            path: path.to_string(),
+            commit_count: 0,
+            contributor_count: 0,
+            lines_added: 0,
+            lines_removed: 0,
+            score: 0.0,
+            complexity: None,
+            risk: 0.0,
+            last_modified,
+            contributors: HashMap::new(),
+            commit_ids: Vec::new(),
+            trend: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum HotspotSort {
+    /// Churn score times code complexity
+    Risk,
+    /// Composite of churn, commit frequency, contributors and recency
+    Score,
+    /// Number of commits touching the file
+    Commits,
+    /// Lines added plus lines removed
+    Churn,
+    /// Number of distinct authors
+    Contributors,
+    /// Most recently modified first
+    Recent,
 }
 
 pub struct HotspotAnalyzer {
     repo: Repository,
     path_filter: Option<String>,
+    paths: PathPatterns,
 }
 
 impl HotspotAnalyzer {
-    pub fn new(repo_path: &str, path_filter: Option<String>) -> Result<Self, git2::Error> {
+    pub fn new(repo_path: &str, path_filter: Option<String>, paths: PathPatterns) -> Result<Self, git2::Error> {
         let path = Path::new(repo_path);
         let repo = Repository::discover(path)?;
         
@@ -52,11 +120,49 @@ impl HotspotAnalyzer {
             }
         });
 
-        Ok(Self { repo, path_filter: normalized_path_filter })
+        Ok(Self { repo, path_filter: normalized_path_filter, paths })
     }
 
-    pub fn analyze(&self, since: &str) -> Result<Vec<FileHotspot>, git2::Error> {
+    /// With `trend`, the analysis covers its windows and every file gets a series
+    pub fn analyze(&self, range: &DateRange, sort_by: HotspotSort, trend: Option<&TrendWindows>) -> Result<Vec<FileHotspot>, git2::Error> {
         let mut hotspots: HashMap<String, FileHotspot> = HashMap::new();
+        let range = trend.map(|windows| windows.span(range)).unwrap_or(*range);
+        self.walk_history(&range, |change| {
+            let (time, churn) = (change.time, change.additions + change.deletions);
+            let hotspot = process_file_change(&mut hotspots, change);
+
+            if let Some(windows) = trend {
+                if hotspot.trend.is_empty() {
+                    hotspot.trend = windows.empty_series();
+                }
+                if let Some(point) = windows.bucket(time).map(|i| &mut hotspot.trend[i]) {
+                    point.commits += 1;
+                    point.churn += churn;
+                }
+            }
+        })?;
+
+        let mut result: Vec<FileHotspot> = hotspots.into_values().collect();
+        let workdir = self.repo.workdir().expect("Repository has no working directory");
+        for hotspot in &mut result {
+            // Files many people touch are harder to keep consistent
+            hotspot.score *= 1.0 + (hotspot.contributor_count as f64).ln();
+            hotspot.complexity = Complexity::measure(&workdir.join(&hotspot.path));
+            hotspot.risk = hotspot.complexity.as_ref()
+                .map_or(hotspot.score, |complexity| hotspot.score * (1.0 + (1.0 + complexity.indentation as f64).ln()));
+        }
+        sort_hotspots(&mut result, sort_by);
+        
+        eprintln!("\nFound {} files with changes", result.len());
+        Ok(result)
+    }
+
+    /// Streams every change to a current source file in the window, newest
+    /// commit first. Changes under a file's old names are reported under its
+    /// current path, and `FileChange::commit` tells commits apart.
+    fn walk_history(&self, range: &DateRange, mut on_change: impl FnMut(FileChange)) -> Result<(), git2::Error> {
+        let filter = PathFilter::new(&self.repo, &self.paths, NON_SOURCE_PATTERNS)
+            .map_err(|e| git2::Error::from_str(&e))?;
         
         // Get repository root path
         let repo_root = self.repo.workdir()
@@ -92,20 +198,6 @@ impl HotspotAnalyzer {
             
             eprintln!("Trying path relative to repo root: {}", relative_to_repo);
             
-            // Check if path exists in git (not just filesystem)
-            let mut check_cmd = std::process::Command::new("git");
-            check_cmd.current_dir(&repo_root)
-                .arg("ls-files")
-                .arg("--error-unmatch")
-                .arg(&relative_to_repo)
-                .stdout(std::process::Stdio::null())
-                .stderr(std::process::Stdio::null());
-            
-            if !check_cmd.status().map(|s| s.success()).unwrap_or(false) {
-                eprintln!("Warning: Path '{}' does not exist in git repository", relative_to_repo);
-                return Ok(Vec::new());
-            }
-            
             Some(relative_to_repo)
         } else {
             None
@@ -113,210 +205,138 @@ impl HotspotAnalyzer {
 
         eprintln!("Analyzing repository at: {}", repo_root);
 
-        // Get list of files that currently exist using git ls-files
-        let mut existing_files = std::collections::HashSet::new();
-        let mut ls_cmd = std::process::Command::new("git");
-        ls_cmd.current_dir(&repo_root)
-            .arg("ls-files");
-        
-        if let Some(ref path) = effective_path_filter {
-            ls_cmd.arg(path);
-        }
+        // Files in the index are the ones that currently exist
+        let index = self.repo.index()?;
+        let existing_files: HashSet<String> = index
+            .iter()
+            .map(|entry| String::from_utf8_lossy(&entry.path).into_owned())
+            .filter(|path| effective_path_filter.as_deref().is_none_or(|prefix| is_under(path, prefix)))
+            .collect();
 
-        let ls_output = ls_cmd.output().expect("Failed to execute git ls-files");
-        let ls_output_str = String::from_utf8_lossy(&ls_output.stdout);
-        
-        for file in ls_output_str.lines() {
-            if !file.trim().is_empty() {
-                existing_files.insert(file.to_string());
+        if let Some(ref path) = effective_path_filter {
+            if existing_files.is_empty() {
+                eprintln!("Warning: Path '{}' does not exist in git repository", path);
+                return Ok(());
             }
         }
 
         eprintln!("Found {} files in current tree", existing_files.len());
 
-        // First, count total commits
-        let mut count_cmd = std::process::Command::new("git");
-        count_cmd.current_dir(&repo_root)
-            .arg("rev-list")
-            .arg("--count")
-            .arg("HEAD");
-
-        if since != "all" {
-            count_cmd.arg(format!("--since={}", since));
-        }
-        if let Some(ref path) = effective_path_filter {
-            count_cmd.arg("--").arg(path);
-        }
-
-        let total_commits = String::from_utf8_lossy(&count_cmd.output().expect("Failed to count commits").stdout)
-            .trim()
-            .parse::<u64>()
-            .unwrap_or(0);
-
-        // Setup progress bar
-        let progress_bar = ProgressBar::new(total_commits);
-        progress_bar.set_style(
-            ProgressStyle::default_bar()
-                .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} commits ({per_sec})")
-                .unwrap()
-                .progress_chars("#>-")
-        );
-        progress_bar.enable_steady_tick(Duration::from_millis(100));
-
-        // Build git log command with numstat to get file changes
-        let mut cmd = std::process::Command::new("git");
-        cmd.current_dir(&repo_root)
-            .arg("log")
-            .arg("--no-merges")
-            .arg("--format=%H%n%at%n%aN%x00")
-            .arg("--numstat")
-            .arg("--no-renames")
-            .arg("--full-history")
-            .arg("--all")  // Include all refs
-            .stdout(std::process::Stdio::piped());
-
-        if since != "all" {
-            cmd.arg(format!("--since={}", since));
-        }
-        if let Some(ref path) = effective_path_filter {
-            cmd.arg("--");
-            // Use a wildcard to catch all files under the directory
-            if !path.contains('.') {  // If it's likely a directory
-                cmd.arg(format!("{}/**", path));
-            } else {
-                cmd.arg(path);
+        // Like `git log --all --no-merges`, limited to the window. Children come
+        // before their parents even with skewed clocks, for the rename mapping below.
+        let mut commits = Vec::new();
+        for oid in walk_all_refs(&self.repo, Sort::TOPOLOGICAL | Sort::TIME)? {
+            let commit = self.repo.find_commit(oid)?;
+            let time = DateTime::<Utc>::from_timestamp(commit.time().seconds(), 0).unwrap_or_default();
+            if commit.parent_count() <= 1 && range.contains(time) {
+                commits.push(oid);
             }
         }
 
-        // Debug: print the command
-        let cmd_str = format!("git log {}",
-            cmd.get_args()
-                .map(|arg| arg.to_string_lossy())
-                .collect::<Vec<_>>()
-                .join(" ")
-        );
-
-        let mut child = cmd.spawn().expect("Failed to spawn git command");
-        let stdout = child.stdout.take().expect("Failed to open stdout");
-        let reader = std::io::BufReader::new(stdout);
-        let mut lines = reader.lines().peekable();
-
-        let mut commit_count = 0;
-        let mut current_hash = String::new();
-        let mut current_time = 0;
-        let mut current_author = String::new();
-
-        while let Some(line_result) = lines.next() {
-            let line = line_result.expect("Failed to read line");
-            
-            // Skip empty lines
-            if line.trim().is_empty() {
-                continue;
-            }
+        let history = diff_history(&self.repo, &commits)?;
+
+        let resolver = AuthorResolver::new(&self.repo);
+        // The history runs newest first, so a rename is seen before the history
+        // under the old path: map every old path to the file's current one
+        let mut renamed: HashMap<String, String> = HashMap::new();
 
-            if line.len() == 40 { // Git hash
-                commit_count += 1;
-                progress_bar.set_position(commit_count);
-                current_hash = line;
-                
-                if let Some(Ok(timestamp)) = lines.next() {
-                    current_time = timestamp.parse().unwrap_or(0);
+        for (commit_count, commit) in (1..).zip(history) {
+            let author = resolver.resolve(&commit.name, &commit.email);
+            let commit_files = commit.files.len();
+
+            for file in commit.files {
+                let current_path = renamed.get(&file.path).cloned().unwrap_or(file.path);
+                if let Some(old_path) = file.old_path {
+                    renamed.insert(old_path, current_path.clone());
                 }
-                if let Some(Ok(author)) = lines.next() {
-                    current_author = author;
+
+                // The path filter applies to the current path, which keeps the
+                // history a file had outside the filtered directory before a move
+                if !is_current_source_file(&existing_files, &filter, &current_path) {
+                    continue;
                 }
-                continue;
-            }
 
-            // Parse stat line
-            if let Some((file_path, _, _)) = parse_stat_line(&line) {
-                let commit_time = DateTime::<Utc>::from_timestamp(current_time, 0)
-                    .expect("Invalid timestamp");
-                process_file_change(&mut hotspots, &existing_files, file_path, commit_time, &current_author);
+                on_change(FileChange {
+                    path: &current_path,
+                    additions: file.additions,
+                    deletions: file.deletions,
+                    commit: commit_count,
+                    commit_files,
+                    time: commit.time,
+                    author: &author,
+                });
             }
         }
 
-        progress_bar.finish_with_message("Analysis complete");
-
-        let mut result: Vec<FileHotspot> = hotspots.into_values().collect();
-        result.sort_by(|a, b| b.commit_count.cmp(&a.commit_count));
-        
-        eprintln!("\nFound {} files with changes", result.len());
-        Ok(result)
+        Ok(())
     }
 }
 
-fn parse_stat_line(line: &str) -> Option<(&str, u32, u32)> {
-    let parts: Vec<&str> = line.split_whitespace().collect();
-    if parts.len() != 3 {
-        return None;
-    }
-
-    let additions = parts[0].parse().unwrap_or(0);
-    let deletions = parts[1].parse().unwrap_or(0);
-    Some((parts[2], additions, deletions))
+fn is_under(path: &str, prefix: &str) -> bool {
+    let prefix = prefix.trim_end_matches('/');
+    path == prefix || path.strip_prefix(prefix).is_some_and(|rest| rest.starts_with('/'))
 }
 
-fn is_source_file(file_path: &str) -> bool {
-    // Files and patterns to explicitly ignore
-    const IGNORED_PATTERNS: &[&str] = &[
-        // Config files
-        "config", "conf", "cfg", "ini", "yaml", "yml", "toml", "json", "xml",
-        "properties", "env", "dist", "plist",
-
-        // Documentation
-        "md", "markdown", "txt", "rst", "adoc", "doc", "docx", "pdf",
-
-        // Data files
-        "csv", "tsv", "sql", "db", "sqlite",
+fn sort_hotspots(hotspots: &mut [FileHotspot], sort_by: HotspotSort) {
+    match sort_by {
+        HotspotSort::Risk => hotspots.sort_by(|a, b| b.risk.total_cmp(&a.risk)),
+        HotspotSort::Score => hotspots.sort_by(|a, b| b.score.total_cmp(&a.score)),
+        HotspotSort::Commits => hotspots.sort_by_key(|h| std::cmp::Reverse(h.commit_count)),
+        HotspotSort::Churn => hotspots.sort_by_key(|h| std::cmp::Reverse(h.lines_added + h.lines_removed)),
+        HotspotSort::Contributors => hotspots.sort_by_key(|h| std::cmp::Reverse(h.contributor_count)),
+        HotspotSort::Recent => hotspots.sort_by_key(|h| std::cmp::Reverse(h.last_modified)),
+    }
+}
 
-        // Lock files
-        "lock", "snapshot",
+/// One commit's worth of changes to a single file
+struct FileChange<'a> {
+    path: &'a str,
+    additions: u32,
+    deletions: u32,
+    commit: u64,
+    /// Files the commit changed in all, including the filtered out ones
+    commit_files: usize,
+    time: DateTime<Utc>,
+    author: &'a str,
+}
 
-        // Build artifacts and dependencies
-        "min.js", "min.css", "map", "bundle.js", "bundle.css",
-        "sum", "mod", "jar", "war", "ear", "class", "pyc", "pyo",
-        "o", "obj", "a", "lib", "so", "dll", "dylib",
+/// A commit's contribution to a file's score. Churn is taken on a log scale,
+/// so ten rewrites outweigh thirty one-line version bumps, and older commits
+/// fade with `RECENCY_HALF_LIFE_DAYS`.
+fn score_commit(change: &FileChange) -> f64 {
+    let churn = (1.0 + (change.additions + change.deletions) as f64).ln();
+    let age_days = (Utc::now() - change.time).num_seconds().max(0) as f64 / 86_400.0;
+    churn * 0.5_f64.powf(age_days / RECENCY_HALF_LIFE_DAYS)
+}
 
-        // Images and media
-        "png", "jpg", "jpeg", "gif", "svg", "ico", "bmp", "webp",
-        "mp3", "mp4", "wav", "avi", "mov", "webm",
-        "ttf", "otf", "woff", "woff2", "eot",
+/// Hotspots are about code, so configuration, documentation and data files
+/// are skipped on top of the shared exclusions
+const NON_SOURCE_PATTERNS: &[&str] = &[
+    // Config files
+    "*config", "*.conf", "*.cfg", "*.ini", "*.yaml", "*.yml", "*.toml", "*.json", "*.xml",
+    "*.properties", "*.env", "*.dist", "*.plist",
 
-        // Archives
-        "zip", "tar", "gz", "rar", "7z",
+    // Documentation
+    "*.md", "*.markdown", "*.txt", "*.rst", "*.adoc", "*.doc", "*.docx",
 
-        // Other
-        "gitignore", "dockerignore", "editorconfig", "DS_Store",
-        "eslintrc", "prettierrc", "babelrc", "browserslistrc",
-    ];
+    // Data files
+    "*.csv", "*.tsv", "*.sql", "*.db", "*.sqlite",
 
-    let lower_path = file_path.to_lowercase();
-    
-    // Check if it matches any ignored pattern
-    for pattern in IGNORED_PATTERNS {
-        if lower_path.ends_with(pattern) {
-            return false;
-        }
-    }
+    // Go module files
+    "go.mod",
+];
 
-    // If the file has an extension and it's not in the ignore list, consider it a source file
-    if let Some(ext) = Path::new(file_path).extension() {
-        if let Some(_) = ext.to_str() {
-            return true;
-        }
-    }
-
-    false
+fn is_source_file(file_path: &str, filter: &PathFilter) -> bool {
+    // Only files with an extension count as source code
+    Path::new(file_path).extension().is_some() && filter.allows(file_path, false)
 }
 
-fn process_file_change(
-    hotspots: &mut HashMap<String, FileHotspot>,
-    existing_files: &std::collections::HashSet<String>,
+/// Whether a path from the history still exists and counts as source code
+fn is_current_source_file(
+    existing_files: &HashSet<String>,
+    filter: &PathFilter,
     file_path: &str,
-    commit_time: DateTime<Utc>,
-    author: &str,
-) {
+) -> bool {
     // Skip if file doesn't exist anymore or isn't a source file
     if !existing_files.contains(file_path) {
         // For directory analysis, check if the file starts with our path
@@ -324,41 +344,42 @@ fn process_file_change(
             let dir_prefix = Path::new(first).parent().map(|p| p.to_string_lossy().to_string());
             if let Some(prefix) = dir_prefix {
                 if !file_path.starts_with(&prefix) {
-                    return;
+                    return false;
                 }
             }
         }
     }
-    
-    if !is_source_file(file_path) {
-        return;
-    }
 
-    let entry = hotspots.entry(file_path.to_string()).or_insert_with(|| FileHotspot {
-        path: file_path.to_string(),
-        commit_count: 0,
-        contributor_count: 0,
-        last_modified: commit_time,
-        contributors: HashMap::new(),
-    });
+    is_source_file(file_path, filter)
+}
+
+fn process_file_change<'a>(hotspots: &'a mut HashMap<String, FileHotspot>, change: FileChange) -> &'a mut FileHotspot {
+    let file_path = change.path;
+
+    let entry = hotspots.entry(file_path.to_string()).or_insert_with(|| FileHotspot::new(file_path, change.time));
 
     entry.commit_count += 1;
-    *entry.contributors.entry(author.to_string()).or_insert(0) += 1;
+    entry.commit_ids.push(change.commit);
+    entry.lines_added += change.additions;
+    entry.lines_removed += change.deletions;
+    entry.score += score_commit(&change);
+    *entry.contributors.entry(change.author.to_string()).or_insert(0) += 1;
     entry.contributor_count = entry.contributors.len();
     
-    if commit_time > entry.last_modified {
-        entry.last_modified = commit_time;
+    if change.time > entry.last_modified {
+        entry.last_modified = change.time;
     }
+    entry
 }
 
-pub fn format_hotspot_report(hotspots: &[FileHotspot], since: &str) -> String {
+pub fn format_hotspot_report(hotspots: &[FileHotspot], since: &str, top: usize, suggestion_rules: &[SuggestionRule]) -> String {
     if hotspots.is_empty() {
         return String::new();
     }
 
-    let mut output = String::from("High Churn Files:\n\n".bold().to_string());
+    let mut output = "High Churn Files:\n\n".bold().to_string();
 
-    for (i, hotspot) in hotspots.iter().enumerate().take(10) {
+    for (i, hotspot) in hotspots.iter().enumerate().take(top) {
         // File path with index
         output.push_str(&format!(
             "{}. {}\n",
@@ -377,22 +398,47 @@ pub fn format_hotspot_report(hotspots: &[FileHotspot], since: &str) -> String {
         };
         output.push_str(&commit_info);
 
+        // Line churn, additions in green and deletions in red
+        output.push_str(&format!(
+            "   - Lines: {} {}\n",
+            format!("+{}", hotspot.lines_added).green(),
+            format!("-{}", hotspot.lines_removed).red()
+        ));
+
+        if !hotspot.trend.is_empty() {
+            output.push_str(&format!(
+                "   - Trend: commits {}  churn {}\n",
+                sparkline(hotspot.trend.iter().map(|p| p.commits as u64)).yellow(),
+                sparkline(hotspot.trend.iter().map(|p| p.churn as u64)).green()
+            ));
+        }
+
         // Contributors count in cyan
         output.push_str(&format!(
             "   - Contributors: {}\n", 
             hotspot.contributor_count.to_string().cyan()
         ));
+
+        if let Some(c) = &hotspot.complexity {
+            output.push_str(&format!(
+                "   - Complexity: {} lines of code, {} functions, max depth {}\n",
+                c.lines_of_code.to_string().cyan(),
+                c.functions.to_string().cyan(),
+                c.max_depth.to_string().cyan()
+            ));
+        }
+
+        output.push_str(&format!(
+            "   - Score: {} (risk {})\n",
+            format!("{:.1}", hotspot.score).bold(),
+            format!("{:.1}", hotspot.risk).bold()
+        ));
         
-        // Suggestions in different colors based on type
-        if hotspot.commit_count > 20 && hotspot.contributor_count > 4 {
-            output.push_str(&"   - Suggestion: ".dimmed().to_string());
-            output.push_str(&"Consider refactoring or adding more tests\n".red().to_string());
-        } else if hotspot.contributor_count > 6 {
-            output.push_str(&"   - Suggestion: ".dimmed().to_string());
-            output.push_str(&"Consider assigning a code owner\n".yellow().to_string());
-        } else if hotspot.commit_count > 15 {
+        // Suggestions in different colors based on severity
+        if let Some(suggestion) = rules::suggest(suggestion_rules, hotspot) {
             output.push_str(&"   - Suggestion: ".dimmed().to_string());
-            output.push_str(&"Review for potential technical debt\n".magenta().to_string());
+            output.push_str(&suggestion);
+            output.push('\n');
         }
         output.push('\n');
     }
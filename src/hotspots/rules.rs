@@ -0,0 +1,320 @@
+use chrono::Utc;
+use colored::*;
+use serde::Deserialize;
+use crate::hotspots::FileHotspot;
+
+/// A suggestion shown under every hotspot matching `when`. Rules are tried
+/// in order and the first match wins.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SuggestionRule {
+    pub when: Condition,
+    /// May refer to fields as `{name}`, e.g. "changed {commits} times"
+    pub message: String,
+    #[serde(default)]
+    pub severity: Severity,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    #[default]
+    Info,
+    Warning,
+    Critical,
+}
+
+/// Comparisons joined by `and`/`&&`, alternatives joined by `or`/`||`,
+/// e.g. "commits > 20 and contributors > 4 or risk >= 300"
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Condition {
+    alternatives: Vec<Vec<Comparison>>,
+}
+
+#[derive(Debug, Clone)]
+struct Comparison {
+    field: String,
+    operator: Operator,
+    value: f64,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Operator {
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+    Equal,
+    NotEqual,
+}
+
+const FIELDS: &[&str] = &[
+    "commits", "contributors", "lines_added", "lines_removed", "churn", "score", "risk",
+    "lines_of_code", "functions", "max_depth", "mean_depth", "days_since_change",
+];
+
+impl TryFrom<String> for Condition {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, String> {
+        let alternatives = text
+            .replace("||", " or ")
+            .replace("&&", " and ")
+            .split(" or ")
+            .map(|alternative| alternative.split(" and ").map(parse_comparison).collect())
+            .collect::<Result<Vec<Vec<Comparison>>, String>>()?;
+        Ok(Self { alternatives })
+    }
+}
+
+fn parse_comparison(text: &str) -> Result<Comparison, String> {
+    let parts: Vec<&str> = text.split_whitespace().collect();
+    let [field, operator, value] = parts.as_slice() else {
+        return Err(format!("expected '<field> <operator> <number>', got '{}'", text.trim()));
+    };
+
+    if !FIELDS.contains(field) {
+        return Err(format!("unknown field '{}', expected one of: {}", field, FIELDS.join(", ")));
+    }
+    let operator = match *operator {
+        ">" => Operator::Greater,
+        ">=" => Operator::GreaterOrEqual,
+        "<" => Operator::Less,
+        "<=" => Operator::LessOrEqual,
+        "==" | "=" => Operator::Equal,
+        "!=" => Operator::NotEqual,
+        other => return Err(format!("unknown operator '{}'", other)),
+    };
+    let value = value.parse().map_err(|_| format!("'{}' is not a number", value))?;
+
+    Ok(Comparison { field: field.to_string(), operator, value })
+}
+
+impl Condition {
+    fn matches(&self, hotspot: &FileHotspot) -> bool {
+        self.alternatives.iter().any(|all| {
+            all.iter().all(|comparison| {
+                // Complexity fields are missing for files that could not be read
+                field_value(hotspot, &comparison.field).is_some_and(|value| comparison.operator.holds(value, comparison.value))
+            })
+        })
+    }
+}
+
+impl Operator {
+    fn holds(self, left: f64, right: f64) -> bool {
+        match self {
+            Operator::Greater => left > right,
+            Operator::GreaterOrEqual => left >= right,
+            Operator::Less => left < right,
+            Operator::LessOrEqual => left <= right,
+            Operator::Equal => left == right,
+            Operator::NotEqual => left != right,
+        }
+    }
+}
+
+fn field_value(hotspot: &FileHotspot, field: &str) -> Option<f64> {
+    let complexity = hotspot.complexity.as_ref();
+    Some(match field {
+        "commits" => hotspot.commit_count as f64,
+        "contributors" => hotspot.contributor_count as f64,
+        "lines_added" => hotspot.lines_added as f64,
+        "lines_removed" => hotspot.lines_removed as f64,
+        "churn" => (hotspot.lines_added + hotspot.lines_removed) as f64,
+        "score" => hotspot.score,
+        "risk" => hotspot.risk,
+        "lines_of_code" => complexity?.lines_of_code as f64,
+        "functions" => complexity?.functions as f64,
+        "max_depth" => complexity?.max_depth as f64,
+        "mean_depth" => complexity?.mean_depth(),
+        "days_since_change" => (Utc::now() - hotspot.last_modified).num_days() as f64,
+        _ => return None,
+    })
+}
+
+impl SuggestionRule {
+    fn new(when: &str, message: &str, severity: Severity) -> Self {
+        Self {
+            when: Condition::try_from(when.to_string()).expect("valid built-in rule"),
+            message: message.to_string(),
+            severity,
+        }
+    }
+
+    /// The message with `{field}` placeholders filled in
+    fn render(&self, hotspot: &FileHotspot) -> String {
+        FIELDS.iter().fold(self.message.clone(), |message, field| {
+            let placeholder = format!("{{{}}}", field);
+            if !message.contains(&placeholder) {
+                return message;
+            }
+            let value = match field_value(hotspot, field) {
+                Some(value) if value.fract() == 0.0 => format!("{}", value),
+                Some(value) => format!("{:.1}", value),
+                None => "?".to_string(),
+            };
+            message.replace(&placeholder, &value)
+        })
+    }
+}
+
+/// Used unless the configuration file has its own `[[hotspots.rules]]`.
+/// Complexity alone is not critical: the file also has to change often.
+pub fn default_rules() -> Vec<SuggestionRule> {
+    vec![
+        SuggestionRule::new(
+            "commits >= 10 and max_depth >= 6 or commits >= 10 and mean_depth >= 2.5",
+            "Consider refactoring or adding more tests: nested {max_depth} levels deep and changed {commits} times",
+            Severity::Critical,
+        ),
+        SuggestionRule::new(
+            "commits >= 10 and lines_of_code > 600 or commits >= 10 and functions > 40",
+            "Consider splitting it up: {lines_of_code} lines of code and {functions} functions, changed {commits} times",
+            Severity::Critical,
+        ),
+        SuggestionRule::new("contributors > 6", "Consider assigning a code owner", Severity::Warning),
+        SuggestionRule::new("commits > 15", "Review for potential technical debt", Severity::Info),
+    ]
+}
+
+/// The first matching rule's message, colored by severity
+pub fn suggest(rules: &[SuggestionRule], hotspot: &FileHotspot) -> Option<String> {
+    let rule = rules.iter().find(|rule| rule.when.matches(hotspot))?;
+    let message = rule.render(hotspot);
+    Some(match rule.severity {
+        Severity::Critical => message.red().to_string(),
+        Severity::Warning => message.yellow().to_string(),
+        Severity::Info => message.magenta().to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hotspots::Complexity;
+
+    fn hotspot(commits: usize, contributors: usize, complexity: Option<Complexity>) -> FileHotspot {
+        let mut hotspot = FileHotspot::new("src/lib.rs", Utc::now());
+        hotspot.commit_count = commits;
+        hotspot.contributor_count = contributors;
+        hotspot.lines_added = 120;
+        hotspot.lines_removed = 30;
+        hotspot.score = 12.5;
+        hotspot.complexity = complexity;
+        hotspot
+    }
+
+    fn complexity(lines_of_code: usize, indentation: usize, max_depth: usize) -> Option<Complexity> {
+        Some(Complexity { lines_of_code, indentation, max_depth, functions: 4 })
+    }
+
+    fn condition(text: &str) -> Result<Condition, String> {
+        Condition::try_from(text.to_string())
+    }
+
+    #[test]
+    fn parses_valid_conditions() {
+        let cases = [
+            ("commits > 20", 1, 1),
+            ("churn == 150", 1, 1),
+            ("risk = 3", 1, 1),
+            ("commits > 20 and contributors > 4", 1, 2),
+            ("commits > 20 && contributors > 4 || risk >= 300", 2, 2),
+            ("max_depth >= 6 or mean_depth >= 2.5 or days_since_change < 30", 3, 1),
+        ];
+        for (text, alternatives, first) in cases {
+            let parsed = condition(text).unwrap_or_else(|e| panic!("{text}: {e}"));
+            assert_eq!(parsed.alternatives.len(), alternatives, "{text}");
+            assert_eq!(parsed.alternatives[0].len(), first, "{text}");
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_conditions() {
+        let cases = [
+            ("commits>=20", "expected '<field> <operator> <number>', got 'commits>=20'"),
+            ("commits >", "expected '<field> <operator> <number>', got 'commits >'"),
+            ("", "expected '<field> <operator> <number>', got ''"),
+            ("commits > 20 and", "expected '<field> <operator> <number>', got 'commits > 20 and'"),
+            ("stars > 5", "unknown field 'stars', expected one of: commits, contributors"),
+            ("commits => 5", "unknown operator '=>'"),
+            ("commits > many", "'many' is not a number"),
+        ];
+        for (text, expected) in cases {
+            let error = condition(text).unwrap_err();
+            assert!(error.starts_with(expected), "{text}: {error}");
+        }
+    }
+
+    #[test]
+    fn rules_from_the_configuration_file_are_validated() {
+        let error = toml::from_str::<SuggestionRule>("when = \"commits >> 5\"\nmessage = \"x\"").unwrap_err();
+        assert!(error.to_string().contains("unknown operator '>>'"), "{error}");
+    }
+
+    #[test]
+    fn matches_thresholds() {
+        let file = hotspot(25, 5, complexity(200, 500, 7));
+        let cases = [
+            ("commits > 20", true),
+            ("commits > 25", false),
+            ("commits >= 25", true),
+            ("commits < 25", false),
+            ("commits <= 25", true),
+            ("commits == 25", true),
+            ("commits != 25", false),
+            ("churn == 150", true),
+            ("score > 12", true),
+            ("mean_depth == 2.5", true),
+            ("days_since_change < 1", true),
+            ("commits > 20 and contributors > 5", false),
+            ("commits > 30 or max_depth >= 7", true),
+            ("commits > 30 or contributors > 10", false),
+            ("commits > 20 and contributors > 5 or lines_of_code == 200", true),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(condition(text).unwrap().matches(&file), expected, "{text}");
+        }
+    }
+
+    #[test]
+    fn complexity_fields_do_not_match_unreadable_files() {
+        let file = hotspot(25, 5, None);
+        assert!(!condition("max_depth >= 0").unwrap().matches(&file));
+        assert!(condition("max_depth >= 0 or commits > 1").unwrap().matches(&file));
+    }
+
+    #[test]
+    fn renders_placeholders() {
+        let rule = SuggestionRule::new("commits > 1", "changed {commits} times, {mean_depth} deep, {stars}", Severity::Info);
+        assert_eq!(rule.render(&hotspot(25, 5, complexity(200, 500, 7))), "changed 25 times, 2.5 deep, {stars}");
+        assert_eq!(rule.render(&hotspot(25, 5, None)), "changed 25 times, ? deep, {stars}");
+    }
+
+    #[test]
+    fn default_critical_rules_need_frequent_changes() {
+        let rules = default_rules();
+        let deep = complexity(200, 700, 9);
+        assert_eq!(suggest(&rules, &hotspot(3, 1, deep.clone())), None);
+        let suggestion = suggest(&rules, &hotspot(12, 1, deep)).unwrap();
+        assert!(suggestion.contains("nested 9 levels deep and changed 12 times"), "{suggestion}");
+
+        let large = Some(Complexity { lines_of_code: 900, indentation: 900, max_depth: 2, functions: 10 });
+        assert_eq!(suggest(&rules, &hotspot(2, 1, large.clone())), None);
+        assert!(suggest(&rules, &hotspot(10, 1, large)).unwrap().contains("Consider splitting it up"));
+    }
+
+    #[test]
+    fn the_first_matching_rule_wins() {
+        let rules = [
+            SuggestionRule::new("commits > 100", "first", Severity::Critical),
+            SuggestionRule::new("commits > 10", "second", Severity::Warning),
+            SuggestionRule::new("commits > 1", "third", Severity::Info),
+        ];
+        let suggestion = suggest(&rules, &hotspot(25, 5, None)).unwrap();
+        assert!(suggestion.contains("second"), "{suggestion}");
+        assert_eq!(suggest(&rules, &hotspot(1, 1, None)), None);
+    }
+}
@@ -0,0 +1,194 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use colored::*;
+use serde::Serialize;
+use crate::hotspots::{FileHotspot, HotspotSort};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum HotspotGrouping {
+    /// One entry per file
+    File,
+    /// Roll files up into their directories
+    Dir,
+}
+
+/// Totals for a directory and everything below it. The root of the tree is ".".
+#[derive(Debug, Serialize)]
+pub struct DirectoryHotspot {
+    pub path: String,
+    /// 0 for the root, 1 for top-level directories, and so on
+    pub depth: usize,
+    pub file_count: usize,
+    /// Distinct commits touching any file below the directory
+    pub commit_count: usize,
+    pub lines_added: u32,
+    pub lines_removed: u32,
+    pub score: f64,
+    pub risk: f64,
+    pub contributors: BTreeSet<String>,
+    pub last_modified: DateTime<Utc>,
+}
+
+#[derive(Default)]
+struct Node {
+    file_count: usize,
+    commits: HashSet<u64>,
+    lines_added: u32,
+    lines_removed: u32,
+    score: f64,
+    risk: f64,
+    contributors: BTreeSet<String>,
+    last_modified: Option<DateTime<Utc>>,
+    children: BTreeMap<String, Node>,
+}
+
+impl Node {
+    fn add(&mut self, hotspot: &FileHotspot) {
+        self.file_count += 1;
+        self.commits.extend(&hotspot.commit_ids);
+        self.lines_added += hotspot.lines_added;
+        self.lines_removed += hotspot.lines_removed;
+        self.score += hotspot.score;
+        self.risk += hotspot.risk;
+        self.contributors.extend(hotspot.contributors.keys().cloned());
+        self.last_modified = self.last_modified.max(Some(hotspot.last_modified));
+    }
+
+    fn to_hotspot(&self, path: String, depth: usize) -> DirectoryHotspot {
+        DirectoryHotspot {
+            path,
+            depth,
+            file_count: self.file_count,
+            commit_count: self.commits.len(),
+            lines_added: self.lines_added,
+            lines_removed: self.lines_removed,
+            score: self.score,
+            risk: self.risk,
+            contributors: self.contributors.clone(),
+            last_modified: self.last_modified.unwrap_or_default(),
+        }
+    }
+}
+
+/// Rolls file hotspots up into directories, at most `max_depth` levels
+/// deep, and flattens the tree depth-first with siblings ranked by `sort_by`
+pub fn group_by_directory(hotspots: &[FileHotspot], max_depth: usize, sort_by: HotspotSort) -> Vec<DirectoryHotspot> {
+    let mut root = Node::default();
+
+    for hotspot in hotspots {
+        root.add(hotspot);
+
+        let mut directories: Vec<&str> = hotspot.path.split('/').collect();
+        directories.pop();
+
+        let mut node = &mut root;
+        for directory in directories.into_iter().take(max_depth) {
+            node = node.children.entry(directory.to_string()).or_default();
+            node.add(hotspot);
+        }
+    }
+
+    let mut result = vec![root.to_hotspot(".".to_string(), 0)];
+    flatten(&root, "", 1, sort_by, &mut result);
+    result
+}
+
+fn flatten(node: &Node, prefix: &str, depth: usize, sort_by: HotspotSort, result: &mut Vec<DirectoryHotspot>) {
+    let mut children: Vec<DirectoryHotspot> = node
+        .children
+        .iter()
+        .map(|(name, child)| child.to_hotspot(format!("{}{}", prefix, name), depth))
+        .collect();
+    sort_directories(&mut children, sort_by);
+
+    for child in children {
+        let name = child.path.rsplit('/').next().unwrap_or_default().to_string();
+        let path = child.path.clone();
+        result.push(child);
+        flatten(&node.children[&name], &format!("{}/", path), depth + 1, sort_by, result);
+    }
+}
+
+/// Keeps the root and the `top` hottest directories below it, in tree order.
+/// Totals never shrink going up the tree, so the parents of the kept
+/// directories rank at least as high and are kept too.
+pub fn truncate_directories(directories: &mut Vec<DirectoryHotspot>, top: usize, sort_by: HotspotSort) {
+    let mut ranked: Vec<usize> = (1..directories.len()).collect();
+    // Stable, so a parent tied with its child still comes first
+    ranked.sort_by(|&a, &b| compare(&directories[a], &directories[b], sort_by));
+    let kept: HashSet<usize> = ranked.into_iter().take(top).collect();
+
+    *directories = std::mem::take(directories)
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| *i == 0 || kept.contains(i))
+        .map(|(_, directory)| directory)
+        .collect();
+}
+
+fn sort_directories(directories: &mut [DirectoryHotspot], sort_by: HotspotSort) {
+    directories.sort_by(|a, b| compare(a, b, sort_by));
+}
+
+/// Hottest first
+fn compare(a: &DirectoryHotspot, b: &DirectoryHotspot, sort_by: HotspotSort) -> Ordering {
+    match sort_by {
+        HotspotSort::Risk => b.risk.total_cmp(&a.risk),
+        HotspotSort::Score => b.score.total_cmp(&a.score),
+        HotspotSort::Commits => b.commit_count.cmp(&a.commit_count),
+        HotspotSort::Churn => (b.lines_added + b.lines_removed).cmp(&(a.lines_added + a.lines_removed)),
+        HotspotSort::Contributors => b.contributors.len().cmp(&a.contributors.len()),
+        HotspotSort::Recent => b.last_modified.cmp(&a.last_modified),
+    }
+}
+
+pub fn format_directory_report(directories: &[DirectoryHotspot], since: &str) -> String {
+    if directories.len() <= 1 {
+        return String::new();
+    }
+
+    let mut output = if since == "all" {
+        "Hot Directories:\n\n".bold().to_string()
+    } else {
+        format!("Hot Directories since {}:\n\n", since).bold().to_string()
+    };
+
+    for (i, directory) in directories.iter().enumerate() {
+        let name = if directory.depth == 0 {
+            directory.path.clone()
+        } else {
+            // Guides for every open ancestor, then a branch for this node
+            let mut guides = String::new();
+            for level in 1..directory.depth {
+                guides.push_str(if has_later_sibling(directories, i, level) { "│   " } else { "    " });
+            }
+            let branch = if has_later_sibling(directories, i, directory.depth) { "├── " } else { "└── " };
+            let last_segment = directory.path.rsplit('/').next().unwrap_or_default();
+            format!("{}{}{}", guides.dimmed(), branch.dimmed(), last_segment)
+        };
+
+        output.push_str(&format!(
+            "{}  {} commits, {} {}, {} files, {} contributors, risk {}\n",
+            name.green(),
+            directory.commit_count.to_string().yellow(),
+            format!("+{}", directory.lines_added).green(),
+            format!("-{}", directory.lines_removed).red(),
+            directory.file_count,
+            directory.contributors.len().to_string().cyan(),
+            format!("{:.1}", directory.risk).bold()
+        ));
+    }
+
+    output
+}
+
+/// Whether the ancestor at `level` of the node at `index` (or the node itself,
+/// when `level` is its own depth) is followed by a sibling further down
+fn has_later_sibling(directories: &[DirectoryHotspot], index: usize, level: usize) -> bool {
+    directories[index + 1..]
+        .iter()
+        .take_while(|d| d.depth >= level)
+        .any(|d| d.depth == level)
+}
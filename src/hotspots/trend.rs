@@ -0,0 +1,114 @@
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, TimeZone, Utc};
+use clap::ValueEnum;
+use serde::Serialize;
+use crate::period::DateRange;
+
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum TrendPeriod {
+    Week,
+    Month,
+    Quarter,
+}
+
+impl TrendPeriod {
+    /// The first day of the calendar window containing `date`
+    fn window_start(&self, date: NaiveDate) -> NaiveDate {
+        match self {
+            TrendPeriod::Week => date - Duration::days(date.weekday().num_days_from_monday() as i64),
+            TrendPeriod::Month => date.with_day(1).expect("every month has a first day"),
+            TrendPeriod::Quarter => {
+                let month = (date.month0() / 3) * 3 + 1;
+                NaiveDate::from_ymd_opt(date.year(), month, 1).expect("valid quarter start")
+            }
+        }
+    }
+
+    fn previous(&self, start: NaiveDate) -> NaiveDate {
+        match self {
+            TrendPeriod::Week => start - Duration::weeks(1),
+            TrendPeriod::Month | TrendPeriod::Quarter => self.window_start(start - Duration::days(1)),
+        }
+    }
+}
+
+/// One window of a file's history
+#[derive(Debug, Clone, Serialize)]
+pub struct TrendPoint {
+    pub start: DateTime<Utc>,
+    pub commits: usize,
+    pub churn: u32,
+}
+
+/// Consecutive calendar windows, oldest first, the last one ending at `end`
+#[derive(Debug, Clone)]
+pub struct TrendWindows {
+    starts: Vec<DateTime<Utc>>,
+    end: DateTime<Utc>,
+}
+
+impl TrendWindows {
+    /// `count` windows of `period` ending with the one containing `end`,
+    /// or now when the range is open-ended
+    pub fn new(period: TrendPeriod, count: usize, range: &DateRange) -> Self {
+        let end = range.end.unwrap_or_else(Utc::now);
+        let mut start = period.window_start((end - Duration::seconds(1)).with_timezone(&Local).date_naive());
+
+        let mut starts = Vec::new();
+        for _ in 0..count.max(1) {
+            starts.push(local_midnight(start));
+            start = period.previous(start);
+        }
+        starts.reverse();
+
+        Self { starts, end }
+    }
+
+    /// The analysis window: all buckets, clipped to the requested range
+    pub fn span(&self, range: &DateRange) -> DateRange {
+        DateRange {
+            start: range.start.max(self.starts.first().copied()),
+            end: Some(self.end),
+        }
+    }
+
+    pub fn bucket(&self, time: DateTime<Utc>) -> Option<usize> {
+        if time >= self.end {
+            return None;
+        }
+        self.starts.iter().rposition(|start| time >= *start)
+    }
+
+    pub fn empty_series(&self) -> Vec<TrendPoint> {
+        self.starts
+            .iter()
+            .map(|start| TrendPoint { start: *start, commits: 0, churn: 0 })
+            .collect()
+    }
+}
+
+/// Bars scaled to the largest value; zero is the lowest bar
+pub fn sparkline(values: impl IntoIterator<Item = u64>) -> String {
+    let values: Vec<u64> = values.into_iter().collect();
+    let max = values.iter().copied().max().unwrap_or(0).max(1);
+    values
+        .iter()
+        .map(|&value| {
+            let level = if value == 0 {
+                0
+            } else {
+                1 + (value * (SPARKS.len() as u64 - 2)).div_ceil(max) as usize
+            };
+            SPARKS[level.min(SPARKS.len() - 1)]
+        })
+        .collect()
+}
+
+fn local_midnight(date: NaiveDate) -> DateTime<Utc> {
+    Local
+        .from_local_datetime(&date.and_hms_opt(0, 0, 0).expect("midnight is a valid time"))
+        .earliest()
+        .map(|instant| instant.with_timezone(&Utc))
+        .unwrap_or_else(|| Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0).expect("midnight is a valid time")))
+}
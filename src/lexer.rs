@@ -0,0 +1,310 @@
+use std::path::Path;
+
+/// What a line of source holds, as far as counting lines of code goes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineKind {
+    /// Has something besides comments, even if it also has a comment
+    Code,
+    Comment,
+    Blank,
+}
+
+/// The comment and string syntax of a family of languages
+struct Syntax {
+    line_comments: &'static [&'static str],
+    /// Opening and closing delimiters
+    block_comments: &'static [(&'static str, &'static str)],
+    /// Comment markers between these are part of a string
+    quotes: &'static [char],
+    /// Block comments only open at the start of a line and are strings
+    /// after code, like Python's triple quotes
+    docstrings: bool,
+}
+
+const C_LIKE: Syntax = Syntax {
+    line_comments: &["//"],
+    block_comments: &[("/*", "*/")],
+    quotes: &['"', '\''],
+    docstrings: false,
+};
+// Single quotes are lifetimes and labels as often as they are characters
+const RUST: Syntax = Syntax {
+    line_comments: &["//"],
+    block_comments: &[("/*", "*/")],
+    quotes: &['"'],
+    docstrings: false,
+};
+const JAVASCRIPT: Syntax = Syntax {
+    line_comments: &["//"],
+    block_comments: &[("/*", "*/")],
+    quotes: &['"', '\'', '`'],
+    docstrings: false,
+};
+const CSS: Syntax = Syntax {
+    line_comments: &[],
+    block_comments: &[("/*", "*/")],
+    quotes: &['"', '\''],
+    docstrings: false,
+};
+const PHP: Syntax = Syntax {
+    line_comments: &["//", "#"],
+    block_comments: &[("/*", "*/")],
+    quotes: &['"', '\''],
+    docstrings: false,
+};
+// Docstrings are documentation, so they count as comments
+const PYTHON: Syntax = Syntax {
+    line_comments: &["#"],
+    block_comments: &[("\"\"\"", "\"\"\""), ("'''", "'''")],
+    quotes: &['"', '\''],
+    docstrings: true,
+};
+const HASH: Syntax = Syntax {
+    line_comments: &["#"],
+    block_comments: &[],
+    quotes: &['"', '\''],
+    docstrings: false,
+};
+const RUBY: Syntax = Syntax {
+    line_comments: &["#"],
+    block_comments: &[("=begin", "=end")],
+    quotes: &['"', '\''],
+    docstrings: false,
+};
+const POWERSHELL: Syntax = Syntax {
+    line_comments: &["#"],
+    block_comments: &[("<#", "#>")],
+    quotes: &['"', '\''],
+    docstrings: false,
+};
+const JULIA: Syntax = Syntax {
+    line_comments: &["#"],
+    block_comments: &[("#=", "=#")],
+    quotes: &['"'],
+    docstrings: false,
+};
+const INI: Syntax = Syntax {
+    line_comments: &[";", "#"],
+    block_comments: &[],
+    quotes: &['"'],
+    docstrings: false,
+};
+const SQL: Syntax = Syntax {
+    line_comments: &["--"],
+    block_comments: &[("/*", "*/")],
+    quotes: &['\''],
+    docstrings: false,
+};
+const LUA: Syntax = Syntax {
+    line_comments: &["--"],
+    block_comments: &[("--[[", "]]")],
+    quotes: &['"', '\''],
+    docstrings: false,
+};
+const HASKELL: Syntax = Syntax {
+    line_comments: &["--"],
+    block_comments: &[("{-", "-}")],
+    quotes: &['"'],
+    docstrings: false,
+};
+const LISP: Syntax = Syntax {
+    line_comments: &[";"],
+    block_comments: &[("#|", "|#")],
+    quotes: &['"'],
+    docstrings: false,
+};
+const PERCENT: Syntax = Syntax {
+    line_comments: &["%"],
+    block_comments: &[],
+    quotes: &['"'],
+    docstrings: false,
+};
+const MARKUP: Syntax = Syntax {
+    line_comments: &[],
+    block_comments: &[("<!--", "-->")],
+    quotes: &[],
+    docstrings: false,
+};
+/// Prose and data without comments: every line with text is counted
+const PLAIN: Syntax = Syntax {
+    line_comments: &[],
+    block_comments: &[],
+    quotes: &[],
+    docstrings: false,
+};
+
+fn syntax_for(path: &Path) -> &'static Syntax {
+    let name = path.file_name().and_then(|name| name.to_str()).unwrap_or("");
+    match name {
+        "Dockerfile" | "Makefile" | "makefile" | "GNUmakefile" | "CMakeLists.txt" | "Gemfile" | "Rakefile"
+        | "Vagrantfile" | "BUILD" | "WORKSPACE" | ".gitignore" | ".dockerignore" => return &HASH,
+        _ => {}
+    }
+
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_ascii_lowercase();
+    match extension.as_str() {
+        "rs" => &RUST,
+        "c" | "h" | "cc" | "cpp" | "cxx" | "hpp" | "hh" | "m" | "mm" | "java" | "kt" | "kts" | "scala" | "cs"
+        | "swift" | "dart" | "go" | "groovy" | "gradle" | "proto" | "zig" | "v" | "sv" | "scss" | "less"
+        | "jsonc" => &C_LIKE,
+        "js" | "jsx" | "ts" | "tsx" | "mjs" | "cjs" | "mts" | "cts" => &JAVASCRIPT,
+        "css" => &CSS,
+        "php" => &PHP,
+        "py" | "pyi" | "pyw" => &PYTHON,
+        "sh" | "bash" | "zsh" | "fish" | "ksh" | "pl" | "pm" | "r" | "ex" | "exs" | "nim" | "tf" | "hcl" | "yml"
+        | "yaml" | "toml" | "cmake" | "mk" | "dockerfile" | "conf" | "cfg" | "properties" => &HASH,
+        "rb" | "rake" | "gemspec" => &RUBY,
+        "ps1" | "psm1" | "psd1" => &POWERSHELL,
+        "jl" => &JULIA,
+        "ini" => &INI,
+        "sql" => &SQL,
+        "lua" => &LUA,
+        "hs" | "elm" | "purs" => &HASKELL,
+        "clj" | "cljs" | "edn" | "lisp" | "el" | "scm" | "rkt" => &LISP,
+        "erl" | "hrl" | "tex" | "sty" => &PERCENT,
+        "html" | "htm" | "xml" | "xhtml" | "svg" | "vue" | "svelte" | "md" | "markdown" => &MARKUP,
+        _ => &PLAIN,
+    }
+}
+
+/// Classifies the lines of a file in order, carrying block comments from
+/// one line to the next. The language is chosen by file name or extension.
+pub struct LineClassifier {
+    syntax: &'static Syntax,
+    /// The closing delimiter of the block comment we are in
+    open_block: Option<&'static str>,
+    /// The closing delimiter of the multi-line string we are in
+    open_string: Option<&'static str>,
+}
+
+impl LineClassifier {
+    pub fn for_path(path: &Path) -> Self {
+        Self { syntax: syntax_for(path), open_block: None, open_string: None }
+    }
+
+    pub fn classify(&mut self, line: &str) -> LineKind {
+        if line.trim().is_empty() {
+            return LineKind::Blank;
+        }
+
+        let mut has_code = false;
+        let mut rest = line;
+        loop {
+            if let Some(close) = self.open_string {
+                has_code = true;
+                match rest.find(close) {
+                    Some(end) => {
+                        rest = &rest[end + close.len()..];
+                        self.open_string = None;
+                    }
+                    None => break,
+                }
+            }
+            if let Some(close) = self.open_block {
+                match rest.find(close) {
+                    Some(end) => {
+                        rest = &rest[end + close.len()..];
+                        self.open_block = None;
+                    }
+                    None => break,
+                }
+            }
+
+            rest = rest.trim_start();
+            let Some(first) = rest.chars().next() else {
+                break;
+            };
+            // Before line comments, which may be prefixes of them, like `--` of `--[[`
+            if let Some((open, close)) = self.syntax.block_comments.iter().find(|(open, _)| rest.starts_with(open)) {
+                rest = &rest[open.len()..];
+                // `query = """SELECT` is a string, not documentation
+                if self.syntax.docstrings && has_code {
+                    self.open_string = Some(close);
+                } else {
+                    self.open_block = Some(close);
+                }
+                continue;
+            }
+            if self.syntax.line_comments.iter().any(|prefix| rest.starts_with(prefix)) {
+                break;
+            }
+
+            has_code = true;
+            rest = if self.syntax.quotes.contains(&first) {
+                skip_string(&rest[first.len_utf8()..], first)
+            } else {
+                &rest[first.len_utf8()..]
+            };
+        }
+
+        if has_code {
+            LineKind::Code
+        } else {
+            LineKind::Comment
+        }
+    }
+}
+
+/// What follows the closing quote, or nothing if the string runs past the line
+fn skip_string(rest: &str, quote: char) -> &str {
+    let mut escaped = false;
+    for (i, c) in rest.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == quote {
+            return &rest[i + c.len_utf8()..];
+        }
+    }
+    ""
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use LineKind::{Blank, Code, Comment};
+
+    fn classify(path: &str, source: &str) -> Vec<LineKind> {
+        let mut classifier = LineClassifier::for_path(Path::new(path));
+        source.lines().map(|line| classifier.classify(line)).collect()
+    }
+
+    #[test]
+    fn single_line_block_comments_close_on_their_line() {
+        let source = "/* header */\nint a;\nint b; /* trailing */\n/* leading */ int c;\n\n/* one\n   two */\n";
+        assert_eq!(classify("a.c", source), vec![Comment, Code, Code, Code, Blank, Comment, Comment]);
+    }
+
+    #[test]
+    fn markdown_bullets_are_text() {
+        let source = "# Title\n\n* first\n* second */\n<!-- hidden -->\n- third\n";
+        assert_eq!(classify("README.md", source), vec![Code, Blank, Code, Code, Comment, Code]);
+    }
+
+    #[test]
+    fn comment_markers_inside_strings_are_code() {
+        assert_eq!(classify("a.rs", "let url = \"http://example.com\";\nlet open = \"/*\";\nlet x = 1;\n"), vec![Code, Code, Code]);
+        assert_eq!(classify("a.sh", "echo \"#1\" # count\n# note\n"), vec![Code, Comment]);
+        assert_eq!(classify("a.js", "const s = `// ${x}`;\n// done\n"), vec![Code, Comment]);
+        assert_eq!(classify("a.c", "char *s = \"say \\\"/*\\\"\";\nint b;\n"), vec![Code, Code]);
+    }
+
+    #[test]
+    fn docstrings_are_comments() {
+        let source = "def f():\n    \"\"\"Does things.\n\n    More.\n    \"\"\"\n    '''Single line.'''\n    return 1\n";
+        assert_eq!(classify("a.py", source), vec![Code, Comment, Blank, Comment, Comment, Comment, Code]);
+    }
+
+    #[test]
+    fn triple_quoted_strings_after_code_are_code() {
+        let source = "query = \"\"\"SELECT *\n# not a comment\nFROM t\n\"\"\"\nx = 1\n";
+        assert_eq!(classify("a.py", source), vec![Code, Code, Code, Code, Code]);
+    }
+
+    #[test]
+    fn files_are_recognized_by_name() {
+        assert_eq!(classify(".gitignore", "# build output\ntarget/\n"), vec![Comment, Code]);
+        assert_eq!(classify("data.mat", "% not a comment\n"), vec![Code]);
+    }
+}
@@ -1,6 +1,7 @@
 use colored::*;
+use std::collections::HashSet;
 use std::fs::File;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 mod commands;
 mod git;
@@ -9,17 +10,83 @@ mod music;
 mod who_knows;
 mod hotspots;
 mod bus_factor;
+mod output;
+mod standup;
+mod period;
+mod path_filter;
+mod lexer;
 
 use commands::parse_cli_args;
-use music::{MusicConfig, commit_to_note, generate_midi, play_midi};
+use git::{GitStats, RepoRecap};
+use music::{commits_to_notes, generate_midi, play_midi};
+use output::OutputFormat;
+use period::DateRange;
+use standup::Summarizer;
 
 fn main() {
     // Parse command line arguments
-    let config = parse_cli_args();
+    let config = match parse_cli_args() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e.red());
+            std::process::exit(1);
+        }
+    };
+
+    // Bus factor looks at a tree, not a time window, so --since/--until do not apply
+    if let Some(path) = config.bus_factor_path {
+        let analyzer = match bus_factor::BusFactorAnalyzer::new(&config.repo_path, config.bus_factor_threshold, config.bus_factor_depth, config.paths.clone()) {
+            Ok(analyzer) => analyzer,
+            Err(e) => {
+                eprintln!("Error initializing bus factor analyzer: {}", e);
+                std::process::exit(1);
+            }
+        };
+
+        let rev = config.bus_factor_rev.as_deref();
+        if !config.bus_factor_leaving.is_empty() {
+            match analyzer.simulate_leave(&path, rev, &config.bus_factor_leaving, config.bus_factor_coverage) {
+                Ok(simulation) => {
+                    let report = output::render(config.format, "Knowledge loss", &simulation.losses, |losses| {
+                        bus_factor::format_leave_report(losses, &simulation.leaving, config.bus_factor_coverage)
+                    });
+                    print_report(report);
+                }
+                Err(e) => {
+                    eprintln!("Error simulating departures: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+
+        match analyzer.analyze_path(&path, rev) {
+            Ok(bus_factors) => {
+                let report = output::render_bus_factor(config.format, &bus_factors, |report| {
+                    bus_factor::format_bus_factor_report(&report.modules, &report.single_owner_files, config.bus_factor_threshold, rev)
+                });
+                print_report(report);
+            }
+            Err(e) => {
+                eprintln!("Error analyzing bus factor: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    // Resolve --since/--until once so every command applies the same window
+    let range = match DateRange::resolve(&config.since, config.until.as_deref(), &config.calendar) {
+        Ok(range) => range,
+        Err(e) => {
+            eprintln!("{}", e.red());
+            std::process::exit(1);
+        }
+    };
 
     // Check if hotspots command was used (with or without path)
     if config.is_hotspots_command {
-        let analyzer = match hotspots::HotspotAnalyzer::new(&config.repo_path, config.hotspots_path) {
+        let analyzer = match hotspots::HotspotAnalyzer::new(&config.repo_path, config.hotspots_path.clone(), config.paths.clone()) {
             Ok(analyzer) => analyzer,
             Err(e) => {
                 eprintln!("Error initializing hotspot analyzer: {}", e);
@@ -27,9 +94,30 @@ fn main() {
             }
         };
 
-        match analyzer.analyze(&config.since) {
-            Ok(hotspots) => {
-                print!("{}", hotspots::format_hotspot_report(&hotspots, &config.since));
+        let trend = config.hotspots_trend.map(|(period, count)| hotspots::TrendWindows::new(period, count, &range));
+        match analyzer.analyze(&range, config.hotspots_sort, trend.as_ref()) {
+            Ok(mut hotspots) => {
+                let report = match config.hotspots_grouping {
+                    hotspots::HotspotGrouping::File => {
+                        if let Some(top) = config.hotspots_top {
+                            hotspots.truncate(top);
+                        }
+                        output::render(config.format, "Hotspots", &hotspots, |h| {
+                            let top = config.hotspots_top.unwrap_or(10);
+                            hotspots::format_hotspot_report(h, &config.period_label(), top, &config.hotspots_rules)
+                        })
+                    }
+                    hotspots::HotspotGrouping::Dir => {
+                        let mut directories = hotspots::group_by_directory(&hotspots, config.hotspots_depth, config.hotspots_sort);
+                        if let Some(top) = config.hotspots_top {
+                            hotspots::truncate_directories(&mut directories, top, config.hotspots_sort);
+                        }
+                        output::render(config.format, "Hot directories", &directories, |d| {
+                            hotspots::format_directory_report(d, &config.period_label())
+                        })
+                    }
+                };
+                print_report(report);
             }
             Err(e) => {
                 eprintln!("Error analyzing hotspots: {}", e);
@@ -39,114 +127,241 @@ fn main() {
         return;
     }
 
+    if let Some(coupling) = &config.coupling {
+        let analyzer = match hotspots::HotspotAnalyzer::new(&config.repo_path, coupling.path.clone(), config.paths.clone()) {
+            Ok(analyzer) => analyzer,
+            Err(e) => {
+                eprintln!("Error initializing coupling analyzer: {}", e);
+                std::process::exit(1);
+            }
+        };
+
+        match analyzer.coupling(&range, coupling.options) {
+            Ok(couplings) => {
+                let report = output::render(config.format, "Change coupling", &couplings, |c| {
+                    hotspots::format_coupling_report(c, &config.period_label())
+                });
+                print_report(report);
+            }
+            Err(e) => {
+                eprintln!("Error analyzing change coupling: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
     if let Some(path) = config.who_knows_path {
-        match who_knows::analyze_file_expertise(&path) {
-            Ok(stats) => who_knows::display_expertise(&path, stats),
+        match who_knows::analyze_file_expertise(&path, &range, &config.paths) {
+            Ok(stats) => {
+                let report = output::render(config.format, &format!("Who knows {}", path), &stats, |s| {
+                    who_knows::format_expertise(&path, s)
+                });
+                print_report(report);
+            }
             Err(e) => eprintln!("Error analyzing file expertise: {}", e),
         }
         return;
     }
 
-    // Handle bus factor analysis
-    if let Some(path) = config.bus_factor_path {
-        let threshold = config.bus_factor_threshold.unwrap_or(80.0);
-        let analyzer = match bus_factor::BusFactorAnalyzer::new(&config.repo_path, threshold) {
-            Ok(analyzer) => analyzer,
+    // Standup notes from your own recent commits
+    if let Some(standup_config) = &config.standup {
+        if let Err(e) = git::validate_repo(&config.repo_path) {
+            eprintln!("{}", e.red());
+            std::process::exit(1);
+        }
+
+        let commits = match git::get_commits(&config.repo_path, &config.authors, &range, false) {
+            Ok(commits) => commits,
             Err(e) => {
-                eprintln!("Error initializing bus factor analyzer: {}", e);
+                eprintln!("{}", e.red());
                 std::process::exit(1);
             }
         };
+        if commits.is_empty() {
+            eprintln!("{}", format!("No commits since '{}' to summarize.", config.period_label()).yellow());
+            return;
+        }
 
-        match analyzer.analyze_path(&path) {
-            Ok(results) => {
-                print!("{}", bus_factor::format_bus_factor_report(&results));
+        let rules = standup::RuleBasedSummarizer;
+        let bullets = match standup_config.backend {
+            standup::Backend::Rules => rules.summarize(&commits, standup_config.diffstat),
+            standup::Backend::Ollama => {
+                let ollama = standup::OllamaSummarizer::new(&standup_config.endpoint, &standup_config.model);
+                ollama.summarize(&commits, standup_config.diffstat).or_else(|e| {
+                    eprintln!("{}", format!("{}; falling back to rule-based notes", e).yellow());
+                    rules.summarize(&commits, standup_config.diffstat)
+                })
             }
+        };
+
+        match bullets {
+            Ok(bullets) if config.format == OutputFormat::Json => print_report(output::render_json(&bullets).map(|json| json + "\n")),
+            Ok(bullets) => print!("{}", standup::format_standup_notes(&bullets)),
             Err(e) => {
-                eprintln!("Error analyzing bus factor: {}", e);
+                eprintln!("{}", e.red());
                 std::process::exit(1);
             }
         }
         return;
     }
 
-    // Validate repository
-    if let Err(e) = git::validate_repo(&config.repo_path) {
-        eprintln!("{}", e.red());
-        std::process::exit(1);
+    // Team recap: everyone's commits, grouped per author
+    if config.is_team_command {
+        if let Err(e) = git::validate_repo(&config.repo_path) {
+            eprintln!("{}", e.red());
+            std::process::exit(1);
+        }
+
+        let commits = match git::get_commits(&config.repo_path, &[], &range, false) {
+            Ok(commits) => commits,
+            Err(e) => {
+                eprintln!("{}", e.red());
+                std::process::exit(1);
+            }
+        };
+        let recaps = git::group_by_author(commits);
+        let totals = GitStats::combine(recaps.iter().map(|recap| &recap.stats));
+
+        if config.format == OutputFormat::Text {
+            println!(
+                "Team recap of commits since '{}' in '{}':",
+                config.period_label().yellow(),
+                config.repo_path.blue()
+            );
+            for recap in &recaps {
+                ui::print_group_header(&recap.author, &recap.stats);
+                for commit in &recap.commits {
+                    ui::print_commit(commit);
+                }
+            }
+            let rows: Vec<(&str, &GitStats)> = recaps.iter().map(|r| (r.author.as_str(), &r.stats)).collect();
+            ui::print_stats_breakdown("Author", &rows, &totals);
+        } else {
+            print_report(output::render_team(config.format, &config.period_label(), &recaps, &totals));
+        }
+        return;
+    }
+
+    // Resolve the repositories to recap
+    let mut repo_paths = config.repo_paths.clone();
+    if let Some(workspace) = &config.workspace {
+        match git::find_repositories(workspace) {
+            Ok(found) => repo_paths.extend(found),
+            Err(e) => {
+                eprintln!("{}", e.red());
+                std::process::exit(1);
+            }
+        }
+        if repo_paths.is_empty() {
+            eprintln!("{}", format!("Error: no Git repositories found below '{}'.", workspace).red());
+            std::process::exit(1);
+        }
+    }
+
+    // `--repo-path` and `--workspace` may name the same repository twice
+    let mut seen = HashSet::new();
+    repo_paths.retain(|path| seen.insert(std::fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path))));
+
+    // Validate repositories
+    for repo_path in &repo_paths {
+        if let Err(e) = git::validate_repo(repo_path) {
+            eprintln!("{}", e.red());
+            std::process::exit(1);
+        }
     }
 
+    let text_output = config.format == OutputFormat::Text;
+    let multi_repo = repo_paths.len() > 1;
+
     // Print initial summary
-    println!(
-        "{}",
-        format!(
+    if text_output {
+        let location = if multi_repo {
+            format!("{} repositories", repo_paths.len())
+        } else {
+            repo_paths[0].clone()
+        };
+        println!(
             "Recap of commits since '{}' by '{}' in '{}':\n",
-            config.since.yellow(),
-            config.author.green(),
-            config.repo_path.blue()
-        )
-    );
-
-    // Get and display commits
-    let commits = git::get_commits(&config.repo_path, &config.author, &config.since, config.show_diff);
-    let mut commit_notes = Vec::new();
-
-    for commit in &commits {
-        ui::print_commit(commit);
-
-        if config.show_diff {
-            if let Some(diff) = git::get_commit_diff(&config.repo_path, &commit.hash) {
-                ui::print_diff(&diff);
-            }
-        }
-
-        // Generate music notes if needed
-        if config.play || config.save_music_path.is_some() {
-            let output = std::process::Command::new("git")
-                .arg("-C")
-                .arg(&config.repo_path)
-                .arg("--no-pager")
-                .arg("show")
-                .arg("--numstat")
-                .arg(&commit.hash)
-                .output()
-                .unwrap();
-
-            if output.status.success() {
-                let stats_output = String::from_utf8_lossy(&output.stdout);
-                for stat_line in stats_output.lines() {
-                    let parts: Vec<&str> = stat_line.split_whitespace().collect();
-                    if parts.len() >= 3 {
-                        if let (Ok(add), Ok(del)) = (parts[0].parse::<i32>(), parts[1].parse::<i32>()) {
-                            let file_ext = Path::new(parts[2])
-                                .extension()
-                                .and_then(|s| s.to_str())
-                                .unwrap_or("unknown");
-                            
-                            let mut note = commit_to_note(add, del, file_ext, &MusicConfig::default());
-                            note.commit_hash = commit.hash.clone();
-                            note.commit_msg = commit.message.clone();
-                            note.file_path = parts[2].to_string();
-                            commit_notes.push(note);
-                        }
-                    }
+            config.period_label().yellow(),
+            config.authors.join(", ").green(),
+            location.blue()
+        );
+    }
+
+    // One pass over each history feeds the listing, diffs, stats and music
+    let show_diff = text_output && config.show_diff;
+    let mut recaps = Vec::new();
+
+    for repo_path in repo_paths {
+        let commits = match git::get_commits(&repo_path, &config.authors, &range, show_diff) {
+            Ok(commits) => commits,
+            Err(e) => {
+                eprintln!("{}", format!("{}: {}", repo_path, e).red());
+                std::process::exit(1);
+            }
+        };
+
+        let stats = GitStats::from_commits(&commits);
+        if text_output && multi_repo {
+            ui::print_group_header(&repo_path, &stats);
+        }
+
+        for commit in &commits {
+            if text_output {
+                ui::print_commit(commit);
+            }
+
+            if show_diff {
+                if let Some(patch) = &commit.patch {
+                    ui::print_diff(patch);
                 }
             }
         }
+
+        recaps.push(RepoRecap { repo_path, commits, stats });
     }
 
-    // Get and display stats
-    let stats = git::get_stats(&config.repo_path, &config.author, &config.since);
-    ui::print_stats(&stats);
+    // Display stats, per repository when there are several
+    if multi_repo {
+        let totals = GitStats::combine(recaps.iter().map(|recap| &recap.stats));
+        if text_output {
+            let rows: Vec<(&str, &GitStats)> = recaps.iter().map(|r| (r.repo_path.as_str(), &r.stats)).collect();
+            ui::print_stats_breakdown("Repository", &rows, &totals);
+        } else {
+            print_report(output::render_workspace(config.format, &config.authors, &config.period_label(), &recaps, &totals));
+        }
+    } else {
+        let recap = &recaps[0];
+        if text_output {
+            ui::print_stats(&recap.stats);
+        } else {
+            print_report(output::render_recap(
+                config.format,
+                &recap.repo_path,
+                &config.authors,
+                &config.period_label(),
+                &recap.commits,
+                &recap.stats,
+            ));
+        }
+    }
 
     // Handle music generation if requested
+    let commit_notes = if config.play || config.save_music_path.is_some() {
+        recaps
+            .iter()
+            .flat_map(|recap| commits_to_notes(&recap.commits, &config.music))
+            .collect()
+    } else {
+        Vec::new()
+    };
     if !commit_notes.is_empty() {
-        let music_config = MusicConfig::default();
-        let midi_with_notes = generate_midi(commit_notes);
+        let midi_with_notes = generate_midi(commit_notes, &config.music);
 
         // Handle playback first if requested
         if config.play {
-            println!("\n{}", "🎵 Playing commit music...".green());
+            eprintln!("\n{}", "🎵 Playing commit music...".green());
             if let Err(e) = play_midi(&midi_with_notes) {
                 eprintln!("{}", format!("Error playing MIDI: {}", e).red());
             }
@@ -170,7 +385,17 @@ fn main() {
             };
 
             midi_with_notes.midi_data.write_std(&mut file).unwrap();
-            println!("\n{}", format!("🎵 MIDI file saved to: {}", path).green());
+            eprintln!("\n{}", format!("🎵 MIDI file saved to: {}", path).green());
+        }
+    }
+}
+
+fn print_report(report: serde_json::Result<String>) {
+    match report {
+        Ok(report) => print!("{}", report),
+        Err(e) => {
+            eprintln!("Error rendering report: {}", e);
+            std::process::exit(1);
         }
     }
-}
\ No newline at end of file
+}
@@ -4,8 +4,10 @@ use midly::{
 use std::time::Duration;
 use rodio::{OutputStream, Sink, source::{SineWave, Source}};
 use std::fs::File;
+use std::path::Path;
 use tempfile::NamedTempFile;
 use colored::*;
+use crate::git::GitCommit;
 
 const BASE_NOTE: u8 = 60; // Middle C
 const VELOCITY: u8 = 100;
@@ -22,6 +24,7 @@ pub struct CommitNote {
     pub deletions: i32,
 }
 
+#[derive(Debug, Clone)]
 pub struct MusicConfig {
     pub base_note: u8,
     pub velocity: u8,
@@ -33,7 +36,7 @@ impl Default for MusicConfig {
         Self {
             base_note: BASE_NOTE,
             velocity: VELOCITY,
-            tempo: 120,
+            tempo: 100,
         }
     }
 }
@@ -80,12 +83,39 @@ pub fn commit_to_note(
     }
 }
 
+/// Turns every changed file of every commit into a note, in commit order
+pub fn commits_to_notes(commits: &[GitCommit], config: &MusicConfig) -> Vec<CommitNote> {
+    let mut notes = Vec::new();
+
+    for commit in commits {
+        for file in &commit.files {
+            // Binary files carry no line counts and have nothing to play
+            if file.binary {
+                continue;
+            }
+
+            let file_ext = Path::new(&file.path)
+                .extension()
+                .and_then(|s| s.to_str())
+                .unwrap_or("unknown");
+
+            let mut note = commit_to_note(file.additions, file.deletions, file_ext, config);
+            note.commit_hash = commit.short_hash().to_string();
+            note.commit_msg = commit.message.clone();
+            note.file_path = file.path.clone();
+            notes.push(note);
+        }
+    }
+
+    notes
+}
+
 pub struct MidiWithNotes {
     pub midi_data: Smf<'static>,
     pub notes: Vec<CommitNote>,
 }
 
-pub fn generate_midi(notes: Vec<CommitNote>) -> MidiWithNotes {
+pub fn generate_midi(notes: Vec<CommitNote>, config: &MusicConfig) -> MidiWithNotes {
     let mut smf = Smf::new(Header::new(
         Format::SingleTrack,
         midly::Timing::Metrical(480.into()),
@@ -96,7 +126,7 @@ pub fn generate_midi(notes: Vec<CommitNote>) -> MidiWithNotes {
     // Set tempo (slower tempo for better clarity)
     track.push(TrackEvent {
         delta: 0.into(),
-        kind: TrackEventKind::Meta(MetaMessage::Tempo((60_000_000 / 100).into())), // 100 BPM
+        kind: TrackEventKind::Meta(MetaMessage::Tempo((60_000_000 / config.tempo).into())),
     });
 
     // Set instruments for each channel using basic GM instruments
@@ -117,7 +147,6 @@ pub fn generate_midi(notes: Vec<CommitNote>) -> MidiWithNotes {
         });
     }
 
-    let mut current_time = 0u32;
     for note in &notes {
         // Note on
         track.push(TrackEvent {
@@ -126,15 +155,14 @@ pub fn generate_midi(notes: Vec<CommitNote>) -> MidiWithNotes {
                 channel: note.channel.into(),
                 message: MidiMessage::NoteOn {
                     key: note.note.into(),
-                    vel: (100u8).into(), // Good velocity for clear sound
+                    vel: note.velocity.into(),
                 },
             },
         });
 
         // Calculate duration based on the commit size but ensure it's audible
-        let duration_ticks = ((note.duration.as_secs_f32() * 480.0) as u32)
-            .max(240)  // Minimum duration (half note)
-            .min(960); // Maximum duration (2 whole notes)
+        // Between a half note and two whole notes
+        let duration_ticks = ((note.duration.as_secs_f32() * 480.0) as u32).clamp(240, 960);
 
         // Note off
         track.push(TrackEvent {
@@ -147,8 +175,6 @@ pub fn generate_midi(notes: Vec<CommitNote>) -> MidiWithNotes {
                 },
             },
         });
-
-        current_time += duration_ticks + 60;
     }
 
     // End of track
@@ -175,43 +201,39 @@ pub fn play_midi(midi_with_notes: &MidiWithNotes) -> Result<(), Box<dyn std::err
     midi_with_notes.midi_data.write_std(&mut file)?;
 
     // Save the file for external playback
-    println!("\n🎵 MIDI file saved temporarily. For better playback, use an external MIDI player.");
-    println!("File location: {}", temp_path.display());
+    eprintln!("\n🎵 MIDI file saved temporarily. For better playback, use an external MIDI player.");
+    eprintln!("File location: {}", temp_path.display());
 
     // Basic audio preview with commit information
     let mut note_index = 0;
     for event in midi_with_notes.midi_data.tracks[0].iter() {
-        if let TrackEventKind::Midi { message, channel } = event.kind {
-            if let MidiMessage::NoteOn { key, vel } = message {
-                if vel.as_int() > 0 {
-                    // Convert MIDI note to frequency
-                    let freq = 440.0 * 2.0f32.powf((key.as_int() as f32 - 69.0) / 12.0);
-                    
-                    // Create a short preview tone
-                    let source = SineWave::new(freq)
-                        .amplify(0.15)
-                        .take_duration(Duration::from_millis(100))
-                        .fade_in(Duration::from_millis(10));
-                    
-                    sink.append(source);
-
-                    // Display commit information
-                    if note_index < midi_with_notes.notes.len() {
-                        let note = &midi_with_notes.notes[note_index];
-                        println!("🎵 {} - {} ({})", 
-                            note.commit_hash.yellow().bold(),
-                            note.commit_msg.cyan(),
-                            format!("+{} -{} {}", 
-                                note.additions.to_string().green(), 
-                                note.deletions.to_string().red(), 
-                                note.file_path.blue()
-                            )
-                        );
-                        note_index += 1;
-                    }
-
-                    std::thread::sleep(Duration::from_millis(150));
+        if let TrackEventKind::Midi { message: MidiMessage::NoteOn { key, vel }, .. } = event.kind {
+            if vel.as_int() > 0 {
+                // Convert MIDI note to frequency
+                let freq = 440.0 * 2.0f32.powf((key.as_int() as f32 - 69.0) / 12.0);
+                
+                // Create a short preview tone
+                let source = SineWave::new(freq)
+                    .amplify(0.15)
+                    .take_duration(Duration::from_millis(100))
+                    .fade_in(Duration::from_millis(10));
+                
+                sink.append(source);
+
+                // Display commit information
+                if note_index < midi_with_notes.notes.len() {
+                    let note = &midi_with_notes.notes[note_index];
+                    eprintln!("🎵 {} - {} (+{} -{} {})",
+                        note.commit_hash.yellow().bold(),
+                        note.commit_msg.cyan(),
+                        note.additions.to_string().green(),
+                        note.deletions.to_string().red(),
+                        note.file_path.blue()
+                    );
+                    note_index += 1;
                 }
+
+                std::thread::sleep(Duration::from_millis(150));
             }
         }
     }
@@ -2,7 +2,7 @@ mod midi;
 
 pub use midi::{
     MusicConfig,
-    commit_to_note,
+    commits_to_notes,
     generate_midi,
     play_midi,
 }; 
\ No newline at end of file
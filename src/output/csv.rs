@@ -0,0 +1,24 @@
+use crate::output::record::Record;
+
+pub fn render_csv<T: Record>(records: &[T]) -> String {
+    let mut output = String::new();
+    push_row(&mut output, T::headers().iter().map(|h| h.to_string()));
+    for record in records {
+        push_row(&mut output, record.fields().into_iter());
+    }
+    output
+}
+
+fn push_row(output: &mut String, fields: impl Iterator<Item = String>) {
+    let row: Vec<String> = fields.map(|f| escape(&f)).collect();
+    output.push_str(&row.join(","));
+    output.push('\n');
+}
+
+fn escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
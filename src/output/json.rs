@@ -0,0 +1,64 @@
+use serde::Serialize;
+use crate::git::{AuthorRecap, GitCommit, GitStats, RepoRecap};
+
+#[derive(Serialize)]
+struct RecapReport<'a> {
+    repo_path: &'a str,
+    authors: &'a [String],
+    since: &'a str,
+    commits: &'a [GitCommit],
+    stats: &'a GitStats,
+}
+
+#[derive(Serialize)]
+struct WorkspaceReport<'a> {
+    authors: &'a [String],
+    since: &'a str,
+    repositories: &'a [RepoRecap],
+    totals: &'a GitStats,
+}
+
+#[derive(Serialize)]
+struct TeamReport<'a> {
+    since: &'a str,
+    authors: &'a [AuthorRecap],
+    totals: &'a GitStats,
+}
+
+pub fn render_json<T: Serialize + ?Sized>(value: &T) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(value)
+}
+
+pub fn render_recap_json(
+    repo_path: &str,
+    authors: &[String],
+    since: &str,
+    commits: &[GitCommit],
+    stats: &GitStats,
+) -> serde_json::Result<String> {
+    render_json(&RecapReport {
+        repo_path,
+        authors,
+        since,
+        commits,
+        stats,
+    })
+}
+
+pub fn render_workspace_json(
+    authors: &[String],
+    since: &str,
+    repositories: &[RepoRecap],
+    totals: &GitStats,
+) -> serde_json::Result<String> {
+    render_json(&WorkspaceReport {
+        authors,
+        since,
+        repositories,
+        totals,
+    })
+}
+
+pub fn render_team_json(since: &str, authors: &[AuthorRecap], totals: &GitStats) -> serde_json::Result<String> {
+    render_json(&TeamReport { since, authors, totals })
+}
@@ -0,0 +1,53 @@
+use crate::git::{GitCommit, GitStats};
+use crate::output::record::Record;
+
+pub fn render_markdown<T: Record>(title: &str, records: &[T]) -> String {
+    let mut output = format!("## {}\n\n", title);
+
+    if records.is_empty() {
+        output.push_str("_No results._\n");
+        return output;
+    }
+
+    let headers = T::headers();
+    output.push_str(&format!("| {} |\n", headers.join(" | ")));
+    output.push_str(&format!("|{}\n", "---|".repeat(headers.len())));
+
+    for record in records {
+        let cells: Vec<String> = record.fields().iter().map(|f| escape(f)).collect();
+        output.push_str(&format!("| {} |\n", cells.join(" | ")));
+    }
+
+    output
+}
+
+/// Renders one commit table per group followed by a totals table
+pub fn render_markdown_groups(
+    title: &str,
+    column: &str,
+    groups: &[(&str, &[GitCommit], &GitStats)],
+    totals: &GitStats,
+) -> String {
+    let mut output = format!("# {}\n\n", title);
+    for (name, commits, _) in groups {
+        output.push_str(&render_markdown(name, commits));
+        output.push('\n');
+    }
+
+    output.push_str(&format!("## Totals\n\n| {} | commits | additions | deletions |\n|---|---|---|---|\n", column));
+    for (name, _, stats) in groups {
+        output.push_str(&format!(
+            "| {} | {} | {} | {} |\n",
+            escape(name), stats.commits_count, stats.total_additions, stats.total_deletions
+        ));
+    }
+    output.push_str(&format!(
+        "| **Total** | {} | {} | {} |\n",
+        totals.commits_count, totals.total_additions, totals.total_deletions
+    ));
+    output
+}
+
+fn escape(cell: &str) -> String {
+    cell.replace('|', "\\|").replace('\n', " ")
+}
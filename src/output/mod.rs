@@ -0,0 +1,138 @@
+mod csv;
+mod json;
+mod markdown;
+mod record;
+
+use clap::ValueEnum;
+use serde::Deserialize;
+use crate::bus_factor::BusFactorReport;
+use crate::git::{AuthorRecap, GitCommit, GitStats, RepoRecap};
+
+pub use csv::render_csv;
+pub use json::{render_json, render_recap_json, render_team_json, render_workspace_json};
+pub use markdown::{render_markdown, render_markdown_groups};
+pub use record::{Record, RepoCommit};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// Colored terminal output
+    Text,
+    /// A single JSON document
+    Json,
+    /// Comma-separated rows with a header line
+    Csv,
+    /// A Markdown table, ready to paste into a wiki
+    Markdown,
+}
+
+/// Renders analysis results in the requested format. `text` builds the
+/// colored terminal report and is only called for `OutputFormat::Text`.
+pub fn render<T: Record>(
+    format: OutputFormat,
+    title: &str,
+    records: &[T],
+    text: impl FnOnce(&[T]) -> String,
+) -> serde_json::Result<String> {
+    Ok(match format {
+        OutputFormat::Text => text(records),
+        OutputFormat::Json => render_json(records)? + "\n",
+        OutputFormat::Csv => render_csv(records),
+        OutputFormat::Markdown => render_markdown(title, records),
+    })
+}
+
+/// Renders a bus factor report: the directory table, then the single-owner
+/// files. CSV puts a blank line between the two tables.
+pub fn render_bus_factor(
+    format: OutputFormat,
+    report: &BusFactorReport,
+    text: impl FnOnce(&BusFactorReport) -> String,
+) -> serde_json::Result<String> {
+    Ok(match format {
+        OutputFormat::Text => text(report),
+        OutputFormat::Json => render_json(report)? + "\n",
+        OutputFormat::Csv => render_csv(&report.modules) + "\n" + &render_csv(&report.single_owner_files),
+        OutputFormat::Markdown => {
+            render_markdown("Bus factor", &report.modules)
+                + "\n"
+                + &render_markdown("Single-owner files", &report.single_owner_files)
+        }
+    })
+}
+
+/// Renders the default recap in a machine-readable format
+pub fn render_recap(
+    format: OutputFormat,
+    repo_path: &str,
+    authors: &[String],
+    since: &str,
+    commits: &[GitCommit],
+    stats: &GitStats,
+) -> serde_json::Result<String> {
+    Ok(match format {
+        OutputFormat::Json => render_recap_json(repo_path, authors, since, commits, stats)? + "\n",
+        OutputFormat::Csv => render_csv(commits),
+        // Text output is printed commit by commit by `ui`
+        OutputFormat::Text | OutputFormat::Markdown => {
+            let mut output = render_markdown(&format!("Recap since {} by {}", since, authors.join(", ")), commits);
+            output.push_str(&format!(
+                "\n**Commits:** {} · **Lines added:** {} · **Lines deleted:** {}\n",
+                stats.commits_count, stats.total_additions, stats.total_deletions
+            ));
+            output
+        }
+    })
+}
+
+/// Renders a multi-repository recap in a machine-readable format
+pub fn render_workspace(
+    format: OutputFormat,
+    authors: &[String],
+    since: &str,
+    recaps: &[RepoRecap],
+    totals: &GitStats,
+) -> serde_json::Result<String> {
+    Ok(match format {
+        OutputFormat::Json => render_workspace_json(authors, since, recaps, totals)? + "\n",
+        OutputFormat::Csv => {
+            let rows: Vec<RepoCommit> = recaps
+                .iter()
+                .flat_map(|recap| recap.commits.iter().map(|commit| RepoCommit { repo: &recap.repo_path, commit }))
+                .collect();
+            render_csv(&rows)
+        }
+        // Text output is printed repository by repository by `ui`
+        OutputFormat::Text | OutputFormat::Markdown => {
+            let groups: Vec<(&str, &[GitCommit], &GitStats)> = recaps
+                .iter()
+                .map(|recap| (recap.repo_path.as_str(), recap.commits.as_slice(), &recap.stats))
+                .collect();
+            render_markdown_groups(&format!("Recap since {} by {}", since, authors.join(", ")), "repo", &groups, totals)
+        }
+    })
+}
+
+/// Renders a per-author team recap in a machine-readable format
+pub fn render_team(
+    format: OutputFormat,
+    since: &str,
+    recaps: &[AuthorRecap],
+    totals: &GitStats,
+) -> serde_json::Result<String> {
+    Ok(match format {
+        OutputFormat::Json => render_team_json(since, recaps, totals)? + "\n",
+        OutputFormat::Csv => {
+            let commits: Vec<&GitCommit> = recaps.iter().flat_map(|recap| &recap.commits).collect();
+            render_csv(&commits)
+        }
+        // Text output is printed author by author by `ui`
+        OutputFormat::Text | OutputFormat::Markdown => {
+            let groups: Vec<(&str, &[GitCommit], &GitStats)> = recaps
+                .iter()
+                .map(|recap| (recap.author.as_str(), recap.commits.as_slice(), &recap.stats))
+                .collect();
+            render_markdown_groups(&format!("Team recap since {}", since), "author", &groups, totals)
+        }
+    })
+}
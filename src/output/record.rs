@@ -0,0 +1,203 @@
+use serde::Serialize;
+use crate::bus_factor::{BusFactorResult, KnowledgeLoss, ModuleBusFactor};
+use crate::git::GitCommit;
+use crate::hotspots::{DirectoryHotspot, FileCoupling, FileHotspot};
+use crate::who_knows::ContributorStats;
+
+/// A flat row that can be written as CSV or as a Markdown table
+pub trait Record: Serialize {
+    fn headers() -> &'static [&'static str];
+    fn fields(&self) -> Vec<String>;
+}
+
+impl<T: Record> Record for &T {
+    fn headers() -> &'static [&'static str] {
+        T::headers()
+    }
+
+    fn fields(&self) -> Vec<String> {
+        (*self).fields()
+    }
+}
+
+impl Record for GitCommit {
+    fn headers() -> &'static [&'static str] {
+        &["hash", "timestamp", "author", "message", "files", "additions", "deletions"]
+    }
+
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.hash.clone(),
+            self.timestamp.to_rfc3339(),
+            self.author.clone(),
+            self.message.clone(),
+            self.files.len().to_string(),
+            self.files.iter().map(|f| f.additions).sum::<i32>().to_string(),
+            self.files.iter().map(|f| f.deletions).sum::<i32>().to_string(),
+        ]
+    }
+}
+
+/// A commit tagged with the repository it came from, for multi-repository recaps
+#[derive(Serialize)]
+pub struct RepoCommit<'a> {
+    pub repo: &'a str,
+    #[serde(flatten)]
+    pub commit: &'a GitCommit,
+}
+
+impl Record for RepoCommit<'_> {
+    fn headers() -> &'static [&'static str] {
+        &["repo", "hash", "timestamp", "author", "message", "files", "additions", "deletions"]
+    }
+
+    fn fields(&self) -> Vec<String> {
+        let mut fields = vec![self.repo.to_string()];
+        fields.extend(self.commit.fields());
+        fields
+    }
+}
+
+impl Record for FileHotspot {
+    fn headers() -> &'static [&'static str] {
+        &[
+            "path", "commits", "contributors", "lines_added", "lines_removed", "score",
+            "lines_of_code", "functions", "max_depth", "indentation", "risk", "last_modified",
+            "commit_trend", "churn_trend",
+        ]
+    }
+
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.path.clone(),
+            self.commit_count.to_string(),
+            self.contributor_count.to_string(),
+            self.lines_added.to_string(),
+            self.lines_removed.to_string(),
+            format!("{:.2}", self.score),
+            optional(self.complexity.as_ref().map(|c| c.lines_of_code)),
+            optional(self.complexity.as_ref().map(|c| c.functions)),
+            optional(self.complexity.as_ref().map(|c| c.max_depth)),
+            optional(self.complexity.as_ref().map(|c| c.indentation)),
+            format!("{:.2}", self.risk),
+            self.last_modified.to_rfc3339(),
+            join(self.trend.iter().map(|point| point.commits)),
+            join(self.trend.iter().map(|point| point.churn)),
+        ]
+    }
+}
+
+impl Record for ContributorStats {
+    fn headers() -> &'static [&'static str] {
+        &["name", "commits", "first_commit", "last_commit"]
+    }
+
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.name.clone(),
+            self.commit_count.to_string(),
+            self.first_commit.to_rfc3339(),
+            self.last_commit.to_rfc3339(),
+        ]
+    }
+}
+
+impl Record for ModuleBusFactor {
+    fn headers() -> &'static [&'static str] {
+        &["path", "depth", "bus_factor", "key_people", "files", "total_lines"]
+    }
+
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.path.clone(),
+            self.depth.to_string(),
+            self.bus_factor.to_string(),
+            self.key_people.join("; "),
+            self.file_count.to_string(),
+            self.total_lines.to_string(),
+        ]
+    }
+}
+
+impl Record for BusFactorResult {
+    fn headers() -> &'static [&'static str] {
+        &["path", "dominant_author", "ownership_percentage", "total_lines"]
+    }
+
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.path.clone(),
+            self.dominant_author.clone(),
+            format!("{:.1}", self.ownership_percentage),
+            self.total_lines.to_string(),
+        ]
+    }
+}
+
+impl Record for KnowledgeLoss {
+    fn headers() -> &'static [&'static str] {
+        &["path", "kind", "total_lines", "lost_lines", "coverage", "candidate"]
+    }
+
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.path.clone(),
+            if self.is_directory { "directory" } else { "file" }.to_string(),
+            self.total_lines.to_string(),
+            self.lost_lines.to_string(),
+            format!("{:.1}", self.coverage),
+            optional(self.candidate.as_ref()),
+        ]
+    }
+}
+
+impl Record for DirectoryHotspot {
+    fn headers() -> &'static [&'static str] {
+        &[
+            "path", "depth", "files", "commits", "contributors", "lines_added", "lines_removed",
+            "score", "risk", "last_modified",
+        ]
+    }
+
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.path.clone(),
+            self.depth.to_string(),
+            self.file_count.to_string(),
+            self.commit_count.to_string(),
+            self.contributors.len().to_string(),
+            self.lines_added.to_string(),
+            self.lines_removed.to_string(),
+            format!("{:.2}", self.score),
+            format!("{:.2}", self.risk),
+            self.last_modified.to_rfc3339(),
+        ]
+    }
+}
+
+impl Record for FileCoupling {
+    fn headers() -> &'static [&'static str] {
+        &["file", "coupled_with", "shared_commits", "file_commits", "coupled_commits", "coupling"]
+    }
+
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.file.clone(),
+            self.coupled_with.clone(),
+            self.shared_commits.to_string(),
+            self.file_commits.to_string(),
+            self.coupled_commits.to_string(),
+            format!("{:.1}", self.coupling),
+        ]
+    }
+}
+
+/// Space-separated series, oldest first
+fn join<T: ToString>(values: impl Iterator<Item = T>) -> String {
+    values.map(|v| v.to_string()).collect::<Vec<_>>().join(" ")
+}
+
+/// Empty cell for values that could not be measured
+fn optional<T: ToString>(value: Option<T>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
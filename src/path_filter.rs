@@ -0,0 +1,131 @@
+use git2::{AttrCheckFlags, AttrValue, Repository};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Files skipped by every analyzer: tool dotfiles, lock files, build output
+/// and binaries. These only ever match file names, never directories. User
+/// excludes can bring any of them back with a `!pattern` line.
+const DEFAULT_EXCLUDES: &[&str] = &[
+    // Tool settings
+    ".gitignore", ".gitattributes", ".gitmodules", ".mailmap", ".dockerignore", ".editorconfig",
+    ".DS_Store", ".eslintrc*", ".prettierrc*", ".babelrc*", ".browserslistrc",
+    // Lock files and snapshots
+    "*.lock", "*.snapshot", "*.sum",
+    // Build artifacts and dependencies
+    "*.min.js", "*.min.css", "*.map", "*.bundle.js", "*.bundle.css",
+    "*.jar", "*.war", "*.ear", "*.class", "*.pyc", "*.pyo",
+    "*.o", "*.obj", "*.a", "*.lib", "*.so", "*.dll", "*.dylib", "*.exe",
+    // Images and media
+    "*.png", "*.jpg", "*.jpeg", "*.gif", "*.svg", "*.ico", "*.bmp", "*.webp",
+    "*.mp3", "*.mp4", "*.wav", "*.avi", "*.mov", "*.webm",
+    "*.ttf", "*.otf", "*.woff", "*.woff2", "*.eot", "*.pdf",
+    // Archives
+    "*.zip", "*.tar", "*.gz", "*.rar", "*.7z",
+];
+
+/// `--include`/`--exclude` globs, in gitignore syntax
+#[derive(Debug, Clone, Default)]
+pub struct PathPatterns {
+    /// When not empty, only files matching one of these are analyzed
+    pub include: Vec<String>,
+    /// Applied after the defaults, so `!pattern` re-includes a default exclude
+    pub exclude: Vec<String>,
+}
+
+/// Decides which repository paths the analyzers look at. Paths marked
+/// `linguist-generated` or `linguist-vendored` in `.gitattributes` are
+/// skipped as well, unless a `!pattern` exclude asks for them.
+pub struct PathFilter<'repo> {
+    repo: &'repo Repository,
+    defaults: Gitignore,
+    include: Option<Gitignore>,
+    exclude: Gitignore,
+    attributes: RefCell<HashMap<String, bool>>,
+}
+
+impl<'repo> PathFilter<'repo> {
+    /// `extra_defaults` are analyzer-specific exclusions, layered over the shared ones
+    pub fn new(repo: &'repo Repository, patterns: &PathPatterns, extra_defaults: &[&str]) -> Result<Self, String> {
+        let root = repo.workdir().unwrap_or_else(|| repo.path());
+
+        let defaults = build(root, DEFAULT_EXCLUDES.iter().chain(extra_defaults), true)?;
+        let include = if patterns.include.is_empty() {
+            None
+        } else {
+            Some(build(root, &patterns.include, false)?)
+        };
+        let exclude = build(root, &patterns.exclude, false)?;
+
+        Ok(Self {
+            repo,
+            defaults,
+            include,
+            exclude,
+            attributes: RefCell::new(HashMap::new()),
+        })
+    }
+
+    /// Whether `path`, relative to the repository root, should be analyzed.
+    /// Directories are only checked against user excludes, so they can be
+    /// pruned without hiding included files below them.
+    pub fn allows(&self, path: &str, is_dir: bool) -> bool {
+        let user = self.exclude.matched_path_or_any_parents(path, is_dir);
+        if user.is_ignore() {
+            return false;
+        }
+
+        if !is_dir {
+            if let Some(include) = &self.include {
+                if !include.matched_path_or_any_parents(path, false).is_ignore() {
+                    return false;
+                }
+            }
+        }
+
+        // `!pattern` wins over the built-in list and .gitattributes
+        if user.is_whitelist() {
+            return true;
+        }
+
+        if is_dir {
+            return true;
+        }
+
+        // Defaults name kinds of files, so a `config/` directory is not a `*config` file
+        !self.defaults.matched(path, false).is_ignore() && !self.is_generated_or_vendored(path)
+    }
+
+    fn is_generated_or_vendored(&self, path: &str) -> bool {
+        if let Some(&marked) = self.attributes.borrow().get(path) {
+            return marked;
+        }
+
+        let marked = ["linguist-generated", "linguist-vendored"].iter().any(|attr| {
+            self.repo
+                .get_attr(Path::new(path), attr, AttrCheckFlags::FILE_THEN_INDEX)
+                .map(|value| matches!(AttrValue::from_string(value), AttrValue::True))
+                .unwrap_or(false)
+        });
+        self.attributes.borrow_mut().insert(path.to_string(), marked);
+        marked
+    }
+}
+
+fn build<I, S>(root: &Path, patterns: I, case_insensitive: bool) -> Result<Gitignore, String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut builder = GitignoreBuilder::new(root);
+    builder
+        .case_insensitive(case_insensitive)
+        .map_err(|e| e.to_string())?;
+    for pattern in patterns {
+        builder
+            .add_line(None, pattern.as_ref())
+            .map_err(|e| format!("Error: invalid path pattern '{}': {}", pattern.as_ref(), e))?;
+    }
+    builder.build().map_err(|e| e.to_string())
+}
@@ -0,0 +1,434 @@
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+
+/// An explicit time window, resolved once from `--since`/`--until` and then
+/// applied the same way by every command. `None` means unbounded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateRange {
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+}
+
+/// How sprints are laid out, for `this-sprint` and `last-sprint`
+#[derive(Debug, Clone, Copy)]
+pub struct SprintCalendar {
+    /// The first day of any sprint; every other sprint is a whole number of lengths away
+    pub anchor: NaiveDate,
+    pub length_days: i64,
+}
+
+impl Default for SprintCalendar {
+    fn default() -> Self {
+        Self {
+            anchor: NaiveDate::from_ymd_opt(2024, 1, 1).expect("valid date"),
+            length_days: 14,
+        }
+    }
+}
+
+/// Which days count as working days, for `last-standup`
+#[derive(Debug, Clone)]
+pub struct WorkWeek {
+    pub days: Vec<Weekday>,
+    pub holidays: Vec<NaiveDate>,
+}
+
+impl Default for WorkWeek {
+    fn default() -> Self {
+        Self {
+            days: vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri],
+            holidays: Vec::new(),
+        }
+    }
+}
+
+impl WorkWeek {
+    pub fn is_working_day(&self, date: NaiveDate) -> bool {
+        self.days.contains(&date.weekday()) && !self.holidays.contains(&date)
+    }
+
+    /// The closest working day strictly before `date`
+    fn previous_working_day(&self, date: NaiveDate) -> Option<NaiveDate> {
+        // A year is enough to cross any realistic run of weekends and holidays
+        (1..=366)
+            .map(|days| date - Duration::days(days))
+            .find(|day| self.is_working_day(*day))
+    }
+}
+
+/// Everything needed to turn a named period into dates
+#[derive(Debug, Clone, Default)]
+pub struct Calendar {
+    pub sprints: SprintCalendar,
+    pub work_week: WorkWeek,
+}
+
+/// A parsed `--since`/`--until` value
+enum Period {
+    /// A point in time, or no bound at all for "all"
+    Instant(Option<DateTime<Utc>>),
+    /// A named window such as "yesterday" or "last-week"
+    Span(DateTime<Utc>, DateTime<Utc>),
+    /// A calendar day such as "2026-01-12"
+    Day(NaiveDate),
+}
+
+impl DateRange {
+    /// Resolves `--since` and the optional `--until`. Named periods carry
+    /// their own end ("yesterday" stops at midnight) unless `--until` is
+    /// given. A bare date starts at its midnight when used as `--since`
+    /// and covers the whole day when used as `--until`, so
+    /// `--since 2026-01-12 --until 2026-01-12` is exactly that day.
+    pub fn resolve(since: &str, until: Option<&str>, calendar: &Calendar) -> Result<Self, String> {
+        Self::resolve_at(since, until, calendar, Local::now())
+    }
+
+    fn resolve_at(since: &str, until: Option<&str>, calendar: &Calendar, now: DateTime<Local>) -> Result<Self, String> {
+        let (start, mut end) = match parse_period(since, now, calendar)? {
+            Period::Instant(instant) => (instant, None),
+            Period::Span(start, end) => (Some(start), Some(end)),
+            Period::Day(date) => (Some(local_midnight(date)?), None),
+        };
+
+        if let Some(until) = until {
+            end = match parse_period(until, now, calendar)? {
+                Period::Instant(instant) => instant,
+                Period::Span(_, end) => Some(end),
+                Period::Day(date) => Some(local_midnight(date + Duration::days(1))?),
+            };
+        }
+
+        if let (Some(start), Some(end)) = (start, end) {
+            if end <= start {
+                return Err(format!("Error: '--until' ({end}) is not after '--since' ({start})."));
+            }
+        }
+
+        Ok(Self { start, end })
+    }
+
+    pub fn contains(&self, instant: DateTime<Utc>) -> bool {
+        self.start.is_none_or(|start| instant >= start) && self.end.is_none_or(|end| instant < end)
+    }
+
+    /// `--since`/`--until` arguments for git commands that still shell out
+    pub fn git_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(start) = self.start {
+            args.push(format!("--since={}", start.to_rfc3339()));
+        }
+        if let Some(end) = self.end {
+            // git's --until is inclusive, ours is exclusive
+            args.push(format!("--until={}", (end - Duration::seconds(1)).to_rfc3339()));
+        }
+        args
+    }
+}
+
+/// What `parse_period` understands, for error messages
+const SUPPORTED_DATES: &str = "all, now, today, yesterday, last-standup, this-week, last-week, \
+this-month, last-month, this-sprint, last-sprint, a weekday such as \"last monday\", \
+\"N units ago\" or N.units.ago, YYYY-MM-DD, \"YYYY-MM-DD HH:MM\" or RFC 3339";
+
+/// Understands "all", "now", "today", "yesterday", "last-standup", "this-week", "last-week",
+/// "this-month", "last-month", "this-sprint", "last-sprint", "[last] <weekday>",
+/// "<n> <unit>s ago" (or git's "<n>.<unit>s.ago"), "2023-01-01", "2023-01-01 12:00" and RFC 3339.
+fn parse_period(value: &str, now: DateTime<Local>, calendar: &Calendar) -> Result<Period, String> {
+    let normalized = value.trim().to_lowercase().replace(['_', ' '], "-");
+    let today = now.date_naive();
+    let span = |start: NaiveDate, end: NaiveDate| -> Result<Period, String> {
+        Ok(Period::Span(local_midnight(start)?, local_midnight(end)?))
+    };
+
+    match normalized.as_str() {
+        "all" => return Ok(Period::Instant(None)),
+        "now" => return Ok(Period::Instant(Some(now.with_timezone(&Utc)))),
+        "today" => return span(today, today + Duration::days(1)),
+        "yesterday" => return span(today - Duration::days(1), today),
+        "last-standup" | "last-workday" => {
+            // Monday mornings reach back to Friday, the day after a holiday past it
+            let day = calendar
+                .work_week
+                .previous_working_day(today)
+                .ok_or("Error: no working days are configured.")?;
+            return Ok(Period::Instant(Some(local_midnight(day)?)));
+        }
+        "this-week" | "last-week" => {
+            let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+            return if normalized == "this-week" {
+                span(monday, monday + Duration::weeks(1))
+            } else {
+                span(monday - Duration::weeks(1), monday)
+            };
+        }
+        "this-month" | "last-month" => {
+            let first = today.with_day(1).expect("every month has a first day");
+            let next = first + Duration::days(32);
+            let next_first = next.with_day(1).expect("every month has a first day");
+            let previous_first = (first - Duration::days(1)).with_day(1).expect("every month has a first day");
+            return if normalized == "this-month" {
+                span(first, next_first)
+            } else {
+                span(previous_first, first)
+            };
+        }
+        "this-sprint" | "last-sprint" => {
+            let sprints = &calendar.sprints;
+            let length = sprints.length_days.max(1);
+            let elapsed = (today - sprints.anchor).num_days();
+            let current = sprints.anchor + Duration::days(elapsed.div_euclid(length) * length);
+            return if normalized == "this-sprint" {
+                span(current, current + Duration::days(length))
+            } else {
+                span(current - Duration::days(length), current)
+            };
+        }
+        _ => {}
+    }
+
+    // The most recent such day before today, like a bare date
+    if let Ok(weekday) = normalized.strip_prefix("last-").unwrap_or(&normalized).parse::<Weekday>() {
+        let back = (today.weekday().num_days_from_monday() + 7 - weekday.num_days_from_monday()) % 7;
+        let back = if back == 0 { 7 } else { back };
+        return Ok(Period::Day(today - Duration::days(back as i64)));
+    }
+
+    let words = value.trim().to_lowercase();
+    if let Some(relative) = words.replace('.', " ").strip_suffix("ago") {
+        return parse_relative(relative.trim(), now).map(|instant| Period::Instant(Some(instant)));
+    }
+
+    if let Ok(instant) = DateTime::parse_from_rfc3339(value.trim()) {
+        return Ok(Period::Instant(Some(instant.with_timezone(&Utc))));
+    }
+
+    for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"] {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(&words, format) {
+            return local_instant(naive).map(|instant| Period::Instant(Some(instant)));
+        }
+    }
+
+    if let Ok(date) = NaiveDate::parse_from_str(&words, "%Y-%m-%d") {
+        return Ok(Period::Day(date));
+    }
+
+    Err(format!("Error: could not understand the date '{value}'. Use {SUPPORTED_DATES}."))
+}
+
+fn parse_relative(relative: &str, now: DateTime<Local>) -> Result<DateTime<Utc>, String> {
+    let parts: Vec<&str> = relative.split_whitespace().collect();
+    let (amount, unit) = match parts.as_slice() {
+        [unit] => (1, *unit),
+        [amount, unit] => {
+            let amount = match *amount {
+                "a" | "an" | "one" => 1,
+                n => n.parse::<i64>().map_err(|_| format!("Error: invalid amount '{n}' in date."))?,
+            };
+            (amount, *unit)
+        }
+        _ => return Err(format!("Error: could not understand the date '{relative} ago'. Use {SUPPORTED_DATES}.")),
+    };
+
+    let duration = match unit.trim_end_matches('s') {
+        "second" | "sec" => Duration::seconds(amount),
+        "minute" | "min" => Duration::minutes(amount),
+        "hour" => Duration::hours(amount),
+        "day" => Duration::days(amount),
+        "week" => Duration::weeks(amount),
+        "month" => Duration::days(amount * 30),
+        "year" => Duration::days(amount * 365),
+        other => return Err(format!("Error: unknown time unit '{other}'.")),
+    };
+
+    Ok((now - duration).with_timezone(&Utc))
+}
+
+fn local_midnight(date: NaiveDate) -> Result<DateTime<Utc>, String> {
+    local_instant(date.and_hms_opt(0, 0, 0).expect("midnight is a valid time"))
+}
+
+fn local_instant(naive: NaiveDateTime) -> Result<DateTime<Utc>, String> {
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|instant| instant.with_timezone(&Utc))
+        .ok_or_else(|| format!("Error: '{naive}' does not exist in the local time zone."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Wednesday, 14 January 2026, mid-afternoon
+    fn now() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 1, 14, 15, 30, 0).single().expect("unambiguous local time")
+    }
+
+    fn midnight(date: &str) -> DateTime<Utc> {
+        local_midnight(date.parse().expect("valid date")).expect("midnight exists")
+    }
+
+    fn resolve(since: &str, until: Option<&str>) -> Result<DateRange, String> {
+        DateRange::resolve_at(since, until, &Calendar::default(), now())
+    }
+
+    fn window(since: &str) -> (Option<DateTime<Utc>>, Option<DateTime<Utc>>) {
+        let range = resolve(since, None).expect("valid period");
+        (range.start, range.end)
+    }
+
+    #[test]
+    fn bare_dates_start_at_midnight_and_cover_the_whole_until_day() {
+        let range = resolve("2026-01-01", Some("2026-01-12")).unwrap();
+        assert_eq!(range.start, Some(midnight("2026-01-01")));
+        assert_eq!(range.end, Some(midnight("2026-01-13")));
+
+        assert_eq!(resolve("2026-01-05", None).unwrap().end, None);
+    }
+
+    #[test]
+    fn the_same_date_twice_is_that_day() {
+        let range = resolve("2026-01-12", Some("2026-01-12")).unwrap();
+        assert_eq!(range, DateRange { start: Some(midnight("2026-01-12")), end: Some(midnight("2026-01-13")) });
+        assert!(range.contains(midnight("2026-01-12")));
+        assert!(!range.contains(midnight("2026-01-13")));
+    }
+
+    #[test]
+    fn until_overrides_the_end_of_a_named_period() {
+        let range = resolve("yesterday", Some("now")).unwrap();
+        assert_eq!(range.start, Some(midnight("2026-01-13")));
+        assert_eq!(range.end, Some(now().with_timezone(&Utc)));
+    }
+
+    #[test]
+    fn until_before_since_is_an_error() {
+        let error = resolve("2026-01-12", Some("2026-01-10")).unwrap_err();
+        assert!(error.contains("is not after"), "{error}");
+    }
+
+    #[test]
+    fn named_periods() {
+        let span = |start, end| (Some(midnight(start)), Some(midnight(end)));
+        assert_eq!(window("all"), (None, None));
+        assert_eq!(window("now"), (Some(now().with_timezone(&Utc)), None));
+        assert_eq!(window("today"), span("2026-01-14", "2026-01-15"));
+        assert_eq!(window("yesterday"), span("2026-01-13", "2026-01-14"));
+        assert_eq!(window("last-standup"), (Some(midnight("2026-01-13")), None));
+        assert_eq!(window("last_workday"), (Some(midnight("2026-01-13")), None));
+        assert_eq!(window("this-week"), span("2026-01-12", "2026-01-19"));
+        assert_eq!(window("Last Week"), span("2026-01-05", "2026-01-12"));
+        assert_eq!(window("this-month"), span("2026-01-01", "2026-02-01"));
+        assert_eq!(window("last-month"), span("2025-12-01", "2026-01-01"));
+        // Two-week sprints counted from Monday, 1 January 2024
+        assert_eq!(window("this-sprint"), span("2026-01-12", "2026-01-26"));
+        assert_eq!(window("last-sprint"), span("2025-12-29", "2026-01-12"));
+    }
+
+    #[test]
+    fn weekdays_are_the_most_recent_one_before_today() {
+        assert_eq!(window("last monday"), (Some(midnight("2026-01-12")), None));
+        assert_eq!(window("friday"), (Some(midnight("2026-01-09")), None));
+        assert_eq!(window("wednesday"), (Some(midnight("2026-01-07")), None));
+    }
+
+    #[test]
+    fn relative_forms() {
+        let ago = |duration: Duration| (Some((now() - duration).with_timezone(&Utc)), None);
+        assert_eq!(window("2 days ago"), ago(Duration::days(2)));
+        assert_eq!(window("1.week.ago"), ago(Duration::weeks(1)));
+        assert_eq!(window("an hour ago"), ago(Duration::hours(1)));
+        assert_eq!(window("minute ago"), ago(Duration::minutes(1)));
+        assert_eq!(window("30 secs ago"), ago(Duration::seconds(30)));
+        assert_eq!(window("3 months ago"), ago(Duration::days(90)));
+        assert_eq!(window("1 year ago"), ago(Duration::days(365)));
+    }
+
+    #[test]
+    fn explicit_instants() {
+        let at = |naive: &str| {
+            local_instant(NaiveDateTime::parse_from_str(naive, "%Y-%m-%d %H:%M:%S").unwrap()).unwrap()
+        };
+        assert_eq!(window("2026-01-12 09:15"), (Some(at("2026-01-12 09:15:00")), None));
+        assert_eq!(window("2026-01-12 09:15:30"), (Some(at("2026-01-12 09:15:30")), None));
+        let rfc3339 = DateTime::parse_from_rfc3339("2026-01-12T09:15:00+02:00").unwrap().with_timezone(&Utc);
+        assert_eq!(window("2026-01-12T09:15:00+02:00"), (Some(rfc3339), None));
+    }
+
+    #[test]
+    fn unknown_dates_list_the_supported_syntax() {
+        for value in ["yesterday 9am", "someday", "last payday", "1 week 2 days ago"] {
+            let error = resolve(value, None).unwrap_err();
+            assert!(error.contains("Use all, now, today"), "{value}: {error}");
+        }
+        assert_eq!(resolve("2 fortnights ago", None).unwrap_err(), "Error: unknown time unit 'fortnight'.");
+        assert_eq!(resolve("x days ago", None).unwrap_err(), "Error: invalid amount 'x' in date.");
+    }
+
+    fn date(date: &str) -> NaiveDate {
+        date.parse().expect("valid date")
+    }
+
+    #[test]
+    fn monday_rolls_back_over_the_weekend() {
+        let week = WorkWeek::default();
+        assert_eq!(week.previous_working_day(date("2026-01-12")), Some(date("2026-01-09")));
+        assert_eq!(week.previous_working_day(date("2026-01-11")), Some(date("2026-01-09")));
+        assert_eq!(week.previous_working_day(date("2026-01-14")), Some(date("2026-01-13")));
+    }
+
+    #[test]
+    fn holidays_are_skipped() {
+        let week = WorkWeek { holidays: vec![date("2026-01-09"), date("2026-01-08")], ..WorkWeek::default() };
+        assert!(!week.is_working_day(date("2026-01-09")));
+        assert_eq!(week.previous_working_day(date("2026-01-12")), Some(date("2026-01-07")));
+    }
+
+    #[test]
+    fn custom_work_days() {
+        let week = WorkWeek {
+            days: vec![Weekday::Sun, Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu],
+            holidays: Vec::new(),
+        };
+        // Sunday reaches back to Thursday, Monday to Sunday
+        assert_eq!(week.previous_working_day(date("2026-01-11")), Some(date("2026-01-08")));
+        assert_eq!(week.previous_working_day(date("2026-01-12")), Some(date("2026-01-11")));
+    }
+
+    #[test]
+    fn no_work_days_is_an_error() {
+        let week = WorkWeek { days: Vec::new(), holidays: Vec::new() };
+        assert_eq!(week.previous_working_day(date("2026-01-12")), None);
+        let calendar = Calendar { work_week: week, ..Calendar::default() };
+        let error = DateRange::resolve_at("last-standup", None, &calendar, now()).unwrap_err();
+        assert_eq!(error, "Error: no working days are configured.");
+    }
+
+    #[test]
+    fn last_standup_follows_the_work_week() {
+        // Wednesday, with Tuesday off
+        let calendar = Calendar {
+            work_week: WorkWeek { holidays: vec![date("2026-01-13")], ..WorkWeek::default() },
+            ..Calendar::default()
+        };
+        let range = DateRange::resolve_at("last-standup", None, &calendar, now()).unwrap();
+        assert_eq!(range.start, Some(midnight("2026-01-12")));
+    }
+
+    #[test]
+    fn sprints_follow_their_anchor_and_length() {
+        let calendar = Calendar {
+            sprints: SprintCalendar { anchor: date("2026-01-07"), length_days: 7 },
+            ..Calendar::default()
+        };
+        let range = DateRange::resolve_at("this-sprint", None, &calendar, now()).unwrap();
+        assert_eq!((range.start, range.end), (Some(midnight("2026-01-14")), Some(midnight("2026-01-21"))));
+
+        // Sprints also run backwards from an anchor in the future
+        let calendar = Calendar {
+            sprints: SprintCalendar { anchor: date("2026-02-02"), length_days: 14 },
+            ..Calendar::default()
+        };
+        let range = DateRange::resolve_at("last-sprint", None, &calendar, now()).unwrap();
+        assert_eq!((range.start, range.end), (Some(midnight("2025-12-22")), Some(midnight("2026-01-05"))));
+    }
+}
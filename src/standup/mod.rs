@@ -0,0 +1,25 @@
+mod ollama;
+mod rules;
+
+use clap::ValueEnum;
+use crate::git::GitCommit;
+
+pub use ollama::OllamaSummarizer;
+pub use rules::RuleBasedSummarizer;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Backend {
+    /// An Ollama-compatible HTTP server, falling back to rules if it is unreachable
+    Ollama,
+    /// Group commits by conventional-commit type and directory, offline
+    Rules,
+}
+
+/// Turns a set of commits into short standup bullet points
+pub trait Summarizer {
+    fn summarize(&self, commits: &[GitCommit], include_diffstat: bool) -> Result<Vec<String>, String>;
+}
+
+pub fn format_standup_notes(bullets: &[String]) -> String {
+    bullets.iter().map(|bullet| format!("- {}\n", bullet)).collect()
+}
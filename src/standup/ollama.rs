@@ -0,0 +1,87 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use crate::git::GitCommit;
+use crate::standup::Summarizer;
+
+const PROMPT: &str = "I'll give you the commits I made recently. Come up with a few topics, \
+in English, that I can read to my team as standup notes. Make it concise: reply with \
+a short bullet list, one topic per line starting with \"- \", and nothing else.";
+
+/// Summarizes through any server speaking Ollama's `/api/generate` protocol
+pub struct OllamaSummarizer {
+    endpoint: String,
+    model: String,
+}
+
+#[derive(Serialize)]
+struct GenerateRequest<'a> {
+    model: &'a str,
+    prompt: String,
+    stream: bool,
+}
+
+#[derive(Deserialize)]
+struct GenerateResponse {
+    response: String,
+}
+
+impl OllamaSummarizer {
+    pub fn new(endpoint: &str, model: &str) -> Self {
+        Self {
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            model: model.to_string(),
+        }
+    }
+
+    fn build_prompt(commits: &[GitCommit], include_diffstat: bool) -> String {
+        let mut prompt = format!("{}\n\nCommits:\n", PROMPT);
+        for commit in commits {
+            prompt.push_str(&format!("* {}\n", commit.message));
+            if include_diffstat {
+                for file in &commit.files {
+                    prompt.push_str(&format!("    {} (+{} -{})\n", file.path, file.additions, file.deletions));
+                }
+            }
+        }
+        prompt
+    }
+}
+
+impl Summarizer for OllamaSummarizer {
+    fn summarize(&self, commits: &[GitCommit], include_diffstat: bool) -> Result<Vec<String>, String> {
+        let request = GenerateRequest {
+            model: &self.model,
+            prompt: Self::build_prompt(commits, include_diffstat),
+            stream: false,
+        };
+
+        let response: GenerateResponse = ureq::post(&format!("{}/api/generate", self.endpoint))
+            .timeout(Duration::from_secs(120))
+            .send_json(&request)
+            .map_err(|e| format!("Error contacting {}: {}", self.endpoint, e))?
+            .into_json()
+            .map_err(|e| format!("Error reading response from {}: {}", self.endpoint, e))?;
+
+        let lines: Vec<&str> = response
+            .response
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect();
+
+        // Keep the bullet list if the model wrapped it in extra prose
+        let bullets: Vec<String> = lines
+            .iter()
+            .filter_map(|line| line.strip_prefix(['-', '*', '•']))
+            .map(|line| line.trim().to_string())
+            .collect();
+
+        if !bullets.is_empty() {
+            Ok(bullets)
+        } else if !lines.is_empty() {
+            Ok(lines.into_iter().map(String::from).collect())
+        } else {
+            Err(format!("Empty response from {}", self.endpoint))
+        }
+    }
+}
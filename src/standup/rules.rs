@@ -0,0 +1,211 @@
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::Path;
+use crate::git::GitCommit;
+use crate::standup::Summarizer;
+
+// Subjects listed per bullet before the rest is summarized as "and N more"
+const MAX_SUBJECTS: usize = 3;
+
+/// Offline summarizer: groups commits by conventional-commit type and by
+/// their scope, or the directory they touched most when they have none
+pub struct RuleBasedSummarizer;
+
+struct Topic {
+    label: &'static str,
+    /// The scope, or the main directory
+    area: Option<String>,
+    subjects: Vec<String>,
+    additions: i32,
+    deletions: i32,
+}
+
+impl Summarizer for RuleBasedSummarizer {
+    fn summarize(&self, commits: &[GitCommit], include_diffstat: bool) -> Result<Vec<String>, String> {
+        let conventional = Regex::new(r"^(\w+)(?:\(([^)]*)\))?!?:\s*(.+)$").expect("valid regex");
+        let mut topics: Vec<Topic> = Vec::new();
+
+        // Oldest first, so each bullet reads in the order the work happened
+        for commit in commits.iter().rev() {
+            let (label, scope, subject) = match conventional.captures(&commit.message) {
+                Some(caps) => (
+                    type_label(&caps[1]),
+                    caps.get(2).map(|scope| scope.as_str().trim().to_string()).filter(|scope| !scope.is_empty()),
+                    caps[3].to_string(),
+                ),
+                None => (type_label(""), None, commit.message.clone()),
+            };
+            let area = scope.or_else(|| main_directory(commit));
+
+            let topic = match topics.iter_mut().find(|t| t.label == label && t.area == area) {
+                Some(topic) => topic,
+                None => {
+                    topics.push(Topic {
+                        label,
+                        area,
+                        subjects: Vec::new(),
+                        additions: 0,
+                        deletions: 0,
+                    });
+                    topics.last_mut().expect("just pushed")
+                }
+            };
+
+            if !topic.subjects.contains(&subject) {
+                topic.subjects.push(subject);
+            }
+            for file in &commit.files {
+                topic.additions += file.additions;
+                topic.deletions += file.deletions;
+            }
+        }
+
+        Ok(topics.iter().map(|topic| format_topic(topic, include_diffstat)).collect())
+    }
+}
+
+fn type_label(commit_type: &str) -> &'static str {
+    match commit_type.to_lowercase().as_str() {
+        "feat" | "feature" => "Added",
+        "fix" | "bugfix" | "hotfix" => "Fixed",
+        "refactor" => "Refactored",
+        "perf" => "Sped up",
+        "docs" | "doc" => "Documented",
+        "test" | "tests" => "Tested",
+        "build" | "ci" => "Build & CI",
+        "chore" | "style" => "Housekeeping",
+        "revert" => "Reverted",
+        _ => "Changes",
+    }
+}
+
+/// The top-level directory (or two levels for `src/`-style layouts) that
+/// the commit touched most
+fn main_directory(commit: &GitCommit) -> Option<String> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+
+    for file in &commit.files {
+        let components: Vec<String> = Path::new(&file.path)
+            .parent()
+            .map(|parent| parent.iter().map(|c| c.to_string_lossy().into_owned()).collect())
+            .unwrap_or_default();
+
+        let directory = match components.as_slice() {
+            [] => continue,
+            [first, second, ..] if matches!(first.as_str(), "src" | "lib" | "app" | "packages" | "crates") => {
+                format!("{}/{}", first, second)
+            }
+            [first, ..] => first.clone(),
+        };
+        *counts.entry(directory).or_insert(0) += 1;
+    }
+
+    counts
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0)))
+        .map(|(directory, _)| directory)
+}
+
+fn format_topic(topic: &Topic, include_diffstat: bool) -> String {
+    let mut bullet = topic.label.to_string();
+    if let Some(area) = &topic.area {
+        bullet.push_str(&format!(" in {}", area));
+    }
+
+    let listed: Vec<&str> = topic.subjects.iter().take(MAX_SUBJECTS).map(String::as_str).collect();
+    bullet.push_str(&format!(": {}", listed.join("; ")));
+    if topic.subjects.len() > MAX_SUBJECTS {
+        bullet.push_str(&format!(" and {} more", topic.subjects.len() - MAX_SUBJECTS));
+    }
+
+    if include_diffstat {
+        bullet.push_str(&format!(" (+{} -{})", topic.additions, topic.deletions));
+    }
+    bullet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::FileChange;
+    use chrono::Utc;
+
+    fn commit(message: &str, files: &[(&str, i32, i32)]) -> GitCommit {
+        GitCommit {
+            hash: "0123456789abcdef".to_string(),
+            message: message.to_string(),
+            relative_time: String::new(),
+            author: "Ann".to_string(),
+            timestamp: Utc::now(),
+            files: files
+                .iter()
+                .map(|(path, additions, deletions)| FileChange {
+                    path: path.to_string(),
+                    additions: *additions,
+                    deletions: *deletions,
+                    binary: false,
+                })
+                .collect(),
+            patch: None,
+        }
+    }
+
+    /// Commits come newest first, like `get_commits` returns them
+    fn summarize(commits: &[GitCommit], include_diffstat: bool) -> Vec<String> {
+        RuleBasedSummarizer.summarize(commits, include_diffstat).unwrap()
+    }
+
+    #[test]
+    fn groups_by_type_and_scope() {
+        let commits = [
+            commit("fix(auth): reject expired tokens", &[("src/auth/token.rs", 4, 1)]),
+            commit("feat(auth): add refresh tokens", &[("src/auth/refresh.rs", 30, 0)]),
+            commit("feat(api)!: version the routes", &[("src/api/routes.rs", 12, 8)]),
+            commit("Feat(auth): add login", &[("src/auth/login.rs", 50, 0)]),
+        ];
+        assert_eq!(
+            summarize(&commits, true),
+            vec![
+                "Added in auth: add login; add refresh tokens (+80 -0)",
+                "Added in api: version the routes (+12 -8)",
+                "Fixed in auth: reject expired tokens (+4 -1)",
+            ]
+        );
+    }
+
+    #[test]
+    fn commits_without_a_scope_group_by_directory() {
+        let commits = [
+            commit("docs: explain setup", &[("docs/setup.md", 10, 0)]),
+            commit("refactor: split parser", &[("src/parser/mod.rs", 40, 30), ("src/parser/lexer.rs", 60, 0)]),
+            commit("refactor(): tidy the parser", &[("src/parser/mod.rs", 5, 5), ("README.md", 1, 1)]),
+            commit("chore: bump version", &[("Cargo.toml", 1, 1)]),
+        ];
+        assert_eq!(
+            summarize(&commits, false),
+            vec!["Housekeeping: bump version", "Refactored in src/parser: tidy the parser; split parser", "Documented in docs: explain setup"]
+        );
+    }
+
+    #[test]
+    fn subjects_without_a_type_fall_back_to_changes() {
+        let commits = [
+            commit("Update the readme: typos", &[("README.md", 2, 2)]),
+            commit("wip", &[("lib/cache/lru.rs", 20, 0)]),
+            commit("unknown: thing", &[("lib/cache/lru.rs", 1, 0)]),
+        ];
+        assert_eq!(
+            summarize(&commits, false),
+            vec!["Changes in lib/cache: thing; wip", "Changes: Update the readme: typos"]
+        );
+    }
+
+    #[test]
+    fn long_topics_are_cut_short_and_repeats_listed_once() {
+        let commits: Vec<GitCommit> = ["one", "two", "three", "two", "four", "five"]
+            .iter()
+            .map(|subject| commit(&format!("fix: {}", subject), &[]))
+            .collect();
+        assert_eq!(summarize(&commits, false), vec!["Fixed: five; four; two and 2 more"]);
+    }
+}
@@ -2,6 +2,7 @@ use colored::*;
 use tabled::{
     Table, Tabled,
     Style, Disable,
+    builder::Builder,
     Modify,
     object::Segment,
     Alignment
@@ -14,10 +15,24 @@ struct StatsRow {
     value: String,
 }
 
+/// Introduces a group of commits (a repository, an author...) in a combined recap
+pub fn print_group_header(name: &str, stats: &GitStats) {
+    println!();
+    println!(
+        "{} {} ({} commits, {} {})",
+        "==>".bold(),
+        name.blue().bold(),
+        stats.commits_count.to_string().yellow(),
+        format!("+{}", stats.total_additions).green(),
+        format!("-{}", stats.total_deletions).red()
+    );
+    println!();
+}
+
 pub fn print_commit(commit: &GitCommit) {
     println!(
         "{} - {} [{} by {}]",
-        commit.hash.yellow().bold(),
+        commit.short_hash().yellow().bold(),
         commit.message.cyan(),
         commit.relative_time.green(),
         commit.author.magenta()
@@ -56,4 +71,35 @@ pub fn print_stats(stats: &GitStats) {
     println!("{}", "====================== STATS ======================".bold());
     println!();
     println!("{table}");
-} 
\ No newline at end of file
+}
+
+/// Prints one stats row per group (repository, author...) followed by a total row
+pub fn print_stats_breakdown(label: &str, rows: &[(&str, &GitStats)], totals: &GitStats) {
+    let mut builder = Builder::default();
+    builder.set_columns([label, "Commits", "Lines added (+)", "Lines deleted (-)"]);
+
+    for (name, stats) in rows {
+        builder.add_record([
+            name.to_string(),
+            stats.commits_count.to_string().yellow().to_string(),
+            stats.total_additions.to_string().green().to_string(),
+            stats.total_deletions.to_string().red().to_string(),
+        ]);
+    }
+    builder.add_record([
+        "Total".bold().to_string(),
+        totals.commits_count.to_string().yellow().bold().to_string(),
+        totals.total_additions.to_string().green().bold().to_string(),
+        totals.total_deletions.to_string().red().bold().to_string(),
+    ]);
+
+    let table = builder
+        .build()
+        .with(Style::modern())
+        .with(Modify::new(Segment::all()).with(Alignment::left()));
+
+    println!();
+    println!("{}", "====================== STATS ======================".bold());
+    println!();
+    println!("{table}");
+}
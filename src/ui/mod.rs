@@ -4,4 +4,6 @@ pub use display::{
     print_commit,
     print_diff,
     print_stats,
+    print_group_header,
+    print_stats_breakdown,
 }; 
\ No newline at end of file
@@ -3,9 +3,12 @@ use std::process::Command;
 use std::path::Path;
 use colored::*;
 use chrono::{DateTime, Local};
+use crate::git::AuthorResolver;
+use crate::path_filter::{PathFilter, PathPatterns};
+use crate::period::DateRange;
 use crate::who_knows::types::ContributorStats;
 
-pub fn analyze_file_expertise(path: &str) -> Result<Vec<ContributorStats>, String> {
+pub fn analyze_file_expertise(path: &str, range: &DateRange, paths: &PathPatterns) -> Result<Vec<ContributorStats>, String> {
     // Check if path exists
     if !Path::new(path).exists() {
         return Err(format!("Path '{}' does not exist", path.blue()));
@@ -13,7 +16,7 @@ pub fn analyze_file_expertise(path: &str) -> Result<Vec<ContributorStats>, Strin
 
     // Check if path is within a git repository
     let git_root = Command::new("git")
-        .args(&["rev-parse", "--show-toplevel"])
+        .args(["rev-parse", "--show-toplevel"])
         .output()
         .map_err(|_| "Not a git repository".red().to_string())?;
 
@@ -21,14 +24,22 @@ pub fn analyze_file_expertise(path: &str) -> Result<Vec<ContributorStats>, Strin
         return Err("Not inside a git repository".red().to_string());
     }
 
+    if let Ok(repo) = git2::Repository::discover(path) {
+        let filter = PathFilter::new(&repo, paths, &[])?;
+        let full_path = std::fs::canonicalize(path).map_err(|e| e.to_string())?;
+        let workdir = repo.workdir().and_then(|dir| std::fs::canonicalize(dir).ok());
+        if let Some(relative) = workdir.as_deref().and_then(|dir| full_path.strip_prefix(dir).ok()) {
+            let relative = relative.to_string_lossy().replace('\\', "/");
+            if !relative.is_empty() && !filter.allows(&relative, full_path.is_dir()) {
+                return Err(format!("'{}' is excluded by the path filters", path.blue()));
+            }
+        }
+    }
+
     let git_log = Command::new("git")
-        .args(&[
-            "log",
-            "--follow",
-            "--format=%H%x09%an%x09%at",
-            "--",
-            path,
-        ])
+        .args(["log", "--follow", "--format=%H%x09%an%x09%ae%x09%at"])
+        .args(range.git_args())
+        .args(["--", path])
         .output()
         .map_err(|e| format!("{}: {}", "Failed to execute git command".red(), e))?;
 
@@ -44,16 +55,17 @@ pub fn analyze_file_expertise(path: &str) -> Result<Vec<ContributorStats>, Strin
         return Err(format!("No git history found for '{}'", path.blue()));
     }
 
+    let resolver = AuthorResolver::discover(".");
     let mut contributors: HashMap<String, ContributorStats> = HashMap::new();
 
     for line in log_output.lines() {
         let parts: Vec<&str> = line.split('\t').collect();
-        if parts.len() != 3 {
+        if parts.len() != 4 {
             continue;
         }
 
-        let name = parts[1].to_string();
-        let timestamp = parts[2]
+        let name = resolver.resolve(parts[1], parts[2]);
+        let timestamp = parts[3]
             .parse::<i64>()
             .map_err(|_| "Failed to parse timestamp".red().to_string())?;
         
@@ -69,7 +81,7 @@ pub fn analyze_file_expertise(path: &str) -> Result<Vec<ContributorStats>, Strin
     }
 
     let mut stats: Vec<ContributorStats> = contributors.into_values().collect();
-    stats.sort_by(|a, b| b.commit_count.cmp(&a.commit_count));
+    stats.sort_by_key(|s| std::cmp::Reverse(s.commit_count));
 
     Ok(stats)
 } 
\ No newline at end of file
@@ -1,22 +1,21 @@
 use colored::*;
 use crate::who_knows::types::ContributorStats;
 
-pub fn display_expertise(path: &str, stats: Vec<ContributorStats>) {
-    println!("\n{}: {}\n", "File".bold(), path.blue());
+pub fn format_expertise(path: &str, stats: &[ContributorStats]) -> String {
+    let mut output = format!("\n{}: {}\n\n", "File".bold(), path.blue());
 
     for (i, stat) in stats.iter().enumerate() {
-        println!("{}. {}", (i + 1).to_string().yellow(), stat.name.green().bold());
-        println!("   {} {}", "•".bright_black(), format!("Changes: {}", stat.commit_count).cyan());
-        println!("   {} {}", "•".bright_black(), format!("Last Touched: {}", stat.format_last_touched()).magenta());
-        println!(
-            "   {} {}\n",
+        output.push_str(&format!("{}. {}\n", (i + 1).to_string().yellow(), stat.name.green().bold()));
+        output.push_str(&format!("   {} {}\n", "•".bright_black(), format!("Changes: {}", stat.commit_count).cyan()));
+        output.push_str(&format!("   {} {}\n", "•".bright_black(), format!("Last Touched: {}", stat.format_last_touched()).magenta()));
+        output.push_str(&format!(
+            "   {} Contribution Duration: {} – {} ({})\n\n",
             "•".bright_black(),
-            format!(
-                "Contribution Duration: {} – {} ({})",
-                stat.first_commit.format("%b %Y").to_string().yellow(),
-                stat.last_commit.format("%b %Y").to_string().yellow(),
-                stat.contribution_duration().bright_white()
-            )
-        );
+            stat.first_commit.format("%b %Y").to_string().yellow(),
+            stat.last_commit.format("%b %Y").to_string().yellow(),
+            stat.contribution_duration().bright_white()
+        ));
     }
-} 
\ No newline at end of file
+
+    output
+}
\ No newline at end of file
@@ -3,5 +3,5 @@ mod analyzer;
 mod display;
 
 pub use analyzer::analyze_file_expertise;
-pub use display::display_expertise;
-pub use types::ContributorStats; 
\ No newline at end of file
+pub use display::format_expertise;
+pub use types::ContributorStats;
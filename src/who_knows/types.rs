@@ -1,6 +1,7 @@
 use chrono::{DateTime, Local};
+use serde::Serialize;
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct ContributorStats {
     pub name: String,
     pub commit_count: u32,